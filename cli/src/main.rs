@@ -1,11 +1,26 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgGroup, Args, Parser, Subcommand};
 use client::ServerHackClient;
 use protos::{
-	Bolt11ReceiveRequest, Bolt11SendRequest, CloseChannelRequest, ForceCloseChannelRequest,
-	GetBalancesRequest, GetNodeIdRequest, GetNodeStatusRequest, GetPaymentDetailsRequest,
-	ListChannelsRequest, OnchainReceiveRequest, OnchainSendRequest, OpenChannelRequest,
-	PaymentsHistoryRequest,
+	channel_config, channel_identifier, onchain_send_request, ApprovePaymentRequest,
+	Bolt11CancelRequest, Bolt11ClaimRequest, Bolt11DecodeRequest, Bolt11FailRequest,
+	Bolt11ProbeRequest, Bolt11ReceiveForHashRequest, Bolt11ReceiveRequest, Bolt11SendRequest,
+	Bolt12SendRequest, ChannelConfig, ChannelIdentifier, CloseChannelRequest, ExportFormat,
+	ExportRequest, ForceCloseChannelRequest, GetBalancesRequest, GetFeeEstimatesRequest,
+	GetGraphChannelRequest, GetGraphNodeRequest, GetNodeIdRequest, GetNodeStatusRequest,
+	GetPaymentDetailsRequest, GetPaymentsByHashRequest, InvoiceStatus, ListAuditLogRequest,
+	ListChannelsRequest, ListClaimablePaymentsRequest, ListClosedChannelsRequest,
+	ListForwardsRequest, ListGraphChannelsRequest, ListGraphNodesRequest, ListInvoicesRequest,
+	ListOnchainTransactionsRequest, ListPendingApprovalsRequest, OnchainReceiveRequest,
+	OnchainSendRequest, OpenChannelRequest, Outpoint, PaymentMetadata, PaymentsHistoryRequest,
+	RejectPaymentRequest, SendingParameters, SignMessageRequest, SpontaneousProbeRequest,
+	SpontaneousSendRequest, UnifiedReceiveRequest, UnifiedSendRequest, UpdateChannelConfigRequest,
+	VerifyAuditLogRequest, VerifySignatureRequest,
 };
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+
+use std::fs;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -21,27 +36,170 @@ struct Cli {
 enum Commands {
 	NodeId,
 	NodeStatus,
+	SignMessage {
+		message: String,
+	},
+	VerifySignature {
+		message: String,
+		signature: String,
+		public_key: String,
+	},
 	NewAddress,
 	SendOnchain {
 		address: String,
 		amount_sats: Option<u64>,
+		#[arg(long, conflicts_with = "confirmation_target_blocks")]
+		fee_rate_sat_per_vb: Option<u64>,
+		#[arg(long)]
+		confirmation_target_blocks: Option<u32>,
 	},
+	OnchainTransactions,
 	Bolt11Receive {
 		description: String,
 		expiry_secs: u32,
 		amount_msat: Option<u64>,
+		#[command(flatten)]
+		metadata: PaymentMetadataArgs,
 	},
 	Bolt11Send {
 		invoice: String,
 		amount_msat: Option<u64>,
+		#[command(flatten)]
+		sending_parameters: SendingParametersArgs,
+		#[command(flatten)]
+		metadata: PaymentMetadataArgs,
+	},
+	Bolt11ReceiveForHash {
+		description: String,
+		expiry_secs: u32,
+		payment_hash: String,
+		amount_msat: Option<u64>,
+		#[command(flatten)]
+		metadata: PaymentMetadataArgs,
+	},
+	ClaimablePayments,
+	Bolt11Claim {
+		payment_hash: String,
+		preimage: String,
+		#[arg(long)]
+		claimable_amount_msat: Option<u64>,
+	},
+	Bolt11Fail {
+		payment_hash: String,
+	},
+	Bolt11Decode {
+		invoice: String,
+	},
+	ListInvoices {
+		#[arg(long, value_parser = parse_invoice_status)]
+		status: Option<InvoiceStatus>,
+	},
+	Bolt11Cancel {
+		payment_hash: String,
+	},
+	Bolt12Send {
+		offer: String,
+		amount_msat: Option<u64>,
+		#[arg(long)]
+		payer_note: Option<String>,
+		#[command(flatten)]
+		metadata: PaymentMetadataArgs,
+	},
+	SpontaneousSend {
+		node_id: String,
+		amount_msat: u64,
+		#[command(flatten)]
+		sending_parameters: SendingParametersArgs,
+		#[command(flatten)]
+		metadata: PaymentMetadataArgs,
+	},
+	UnifiedReceive {
+		amount_sats: u64,
+		description: String,
+		expiry_secs: u32,
+		/// Render the URI as a QR code.
+		#[arg(long)]
+		qr: bool,
+	},
+	UnifiedSend {
+		uri: String,
+	},
+	Bolt11Probe {
+		invoice: String,
+		amount_msat: Option<u64>,
+	},
+	SpontaneousProbe {
+		node_id: String,
+		amount_msat: u64,
 	},
 	NodeBalances,
-	PaymentsHistory,
+	FeeEstimates,
+	PaymentsHistory {
+		/// Only show payments carrying this label.
+		#[arg(long)]
+		label: Option<String>,
+		/// Only show payments with this external id.
+		#[arg(long)]
+		external_id: Option<String>,
+	},
 	PaymentDetails {
 		#[arg(short, long)]
 		payment_id: String,
 	},
+	PaymentsByHash {
+		payment_hash: String,
+	},
+	/// List forwarded payments, newest first, with fee income aggregated by channel and peer.
+	ListForwards {
+		/// Only include forwards settled at or after this unix timestamp or UTC date (YYYY-MM-DD).
+		#[arg(long, value_parser = parse_timestamp)]
+		start: Option<u64>,
+		/// Only include forwards settled before this unix timestamp or UTC date (YYYY-MM-DD).
+		#[arg(long, value_parser = parse_timestamp)]
+		end: Option<u64>,
+		#[arg(long, default_value_t = 0)]
+		offset: u32,
+		#[arg(long)]
+		limit: Option<u32>,
+	},
+	/// Export settled payments, forwards and on-chain activity for accounting.
+	Export {
+		/// Only export entries settled at or after this unix timestamp or UTC date (YYYY-MM-DD).
+		#[arg(long, value_parser = parse_timestamp)]
+		start: Option<u64>,
+		/// Only export entries settled before this unix timestamp or UTC date (YYYY-MM-DD).
+		#[arg(long, value_parser = parse_timestamp)]
+		end: Option<u64>,
+		/// Either csv or jsonl.
+		#[arg(long, default_value = "csv", value_parser = parse_export_format)]
+		format: ExportFormat,
+		/// Write the export to this file instead of stdout.
+		#[arg(long)]
+		output: Option<PathBuf>,
+	},
+	/// List outgoing payments waiting for approval under the spending policy.
+	PendingApprovals,
+	ApprovePayment {
+		id: String,
+		#[arg(long)]
+		approval_token: String,
+	},
+	RejectPayment {
+		id: String,
+		#[arg(long)]
+		approval_token: String,
+	},
+	/// List the audit log of state-changing calls, oldest first.
+	AuditLog {
+		#[arg(long, default_value_t = 0)]
+		offset: u32,
+		#[arg(long)]
+		limit: Option<u32>,
+	},
+	/// Check that no entry of the audit log was modified or removed.
+	VerifyAuditLog,
 	ListChannels,
+	ListClosedChannels,
 	OpenChannel {
 		#[arg(short, long)]
 		node_id: String,
@@ -53,21 +211,133 @@ enum Commands {
 		push_to_counterparty_msat: Option<u64>,
 		#[arg(long)]
 		announce_channel: bool,
+		#[arg(long)]
+		forwarding_fee_proportional_millionths: Option<u32>,
+		#[arg(long)]
+		forwarding_fee_base_msat: Option<u32>,
+		#[arg(long)]
+		cltv_expiry_delta: Option<u32>,
+		#[arg(long)]
+		max_dust_htlc_exposure_msat: Option<u64>,
 	},
+	#[command(group(ArgGroup::new("channel").required(true).args(["user_channel_id", "channel_id", "funding_txo", "all_channels"])))]
 	CloseChannel {
 		#[arg(short, long)]
-		user_channel_id: String,
-		#[arg(short, long)]
-		counterparty_node_id: String,
+		user_channel_id: Option<String>,
+		#[arg(long)]
+		channel_id: Option<String>,
+		#[arg(long, value_name = "TXID:VOUT", value_parser = parse_outpoint)]
+		funding_txo: Option<Outpoint>,
+		#[arg(short, long, required_if_eq("all_channels", "true"))]
+		counterparty_node_id: Option<String>,
+		#[arg(long)]
+		all_channels: bool,
 	},
+	#[command(group(ArgGroup::new("channel").required(true).args(["user_channel_id", "channel_id", "funding_txo", "all_channels"])))]
 	ForceCloseChannel {
+		#[arg(short, long)]
+		user_channel_id: Option<String>,
+		#[arg(long)]
+		channel_id: Option<String>,
+		#[arg(long, value_name = "TXID:VOUT", value_parser = parse_outpoint)]
+		funding_txo: Option<Outpoint>,
+		#[arg(short, long, required_if_eq("all_channels", "true"))]
+		counterparty_node_id: Option<String>,
+		#[arg(long)]
+		all_channels: bool,
+	},
+	UpdateChannelConfig {
 		#[arg(short, long)]
 		user_channel_id: String,
 		#[arg(short, long)]
 		counterparty_node_id: String,
+		#[arg(long)]
+		forwarding_fee_proportional_millionths: Option<u32>,
+		#[arg(long)]
+		forwarding_fee_base_msat: Option<u32>,
+		#[arg(long)]
+		cltv_expiry_delta: Option<u32>,
+		#[arg(long, conflicts_with = "max_dust_htlc_exposure_fee_rate_multiplier")]
+		max_dust_htlc_exposure_msat: Option<u64>,
+		#[arg(long)]
+		max_dust_htlc_exposure_fee_rate_multiplier: Option<u64>,
+		#[arg(long)]
+		force_close_avoidance_max_fee_satoshis: Option<u64>,
+		#[arg(long)]
+		accept_underpaying_htlcs: Option<bool>,
+	},
+	GraphChannels {
+		#[arg(long, default_value_t = 0)]
+		offset: u32,
+		#[arg(long)]
+		limit: Option<u32>,
+	},
+	GraphChannel {
+		short_channel_id: u64,
+	},
+	GraphNodes {
+		#[arg(long, default_value_t = 0)]
+		offset: u32,
+		#[arg(long)]
+		limit: Option<u32>,
+	},
+	GraphNode {
+		node_id: String,
 	},
 }
 
+#[derive(Args, Debug)]
+struct SendingParametersArgs {
+	#[arg(long)]
+	max_total_routing_fee_msat: Option<u64>,
+	#[arg(long)]
+	max_total_routing_fee_ppm: Option<u64>,
+	#[arg(long)]
+	max_total_cltv_expiry_delta: Option<u32>,
+	#[arg(long)]
+	max_path_count: Option<u32>,
+}
+
+impl SendingParametersArgs {
+	fn into_proto(self) -> Option<SendingParameters> {
+		let sending_parameters = SendingParameters {
+			max_total_routing_fee_msat: self.max_total_routing_fee_msat,
+			max_total_cltv_expiry_delta: self.max_total_cltv_expiry_delta,
+			max_total_routing_fee_ppm: self.max_total_routing_fee_ppm,
+			max_path_count: self.max_path_count,
+		};
+		if sending_parameters == SendingParameters::default() {
+			None
+		} else {
+			Some(sending_parameters)
+		}
+	}
+}
+
+#[derive(Args, Debug)]
+struct PaymentMetadataArgs {
+	/// A label to attach to the payment. May be given multiple times.
+	#[arg(long = "label")]
+	labels: Vec<String>,
+	#[arg(long)]
+	memo: Option<String>,
+	/// An identifier of the payment in an external system, such as an order id.
+	#[arg(long)]
+	external_id: Option<String>,
+}
+
+impl PaymentMetadataArgs {
+	fn into_proto(self) -> Option<PaymentMetadata> {
+		let metadata =
+			PaymentMetadata { labels: self.labels, memo: self.memo, external_id: self.external_id };
+		if metadata == PaymentMetadata::default() {
+			None
+		} else {
+			Some(metadata)
+		}
+	}
+}
+
 #[tokio::main]
 async fn main() {
 	let cli = Cli::parse();
@@ -94,7 +364,34 @@ async fn main() {
 				},
 			};
 		},
-		Commands::NewAddress {} => {
+		Commands::SignMessage { message } => {
+			match client.sign_message(SignMessageRequest { message: message.into_bytes() }).await {
+				Ok(response) => {
+					println!("Signature: {}", response.signature);
+				},
+				Err(e) => {
+					eprintln!("Error signing message: {:?}", e);
+				},
+			};
+		},
+		Commands::VerifySignature { message, signature, public_key } => {
+			match client
+				.verify_signature(VerifySignatureRequest {
+					message: message.into_bytes(),
+					signature,
+					public_key,
+				})
+				.await
+			{
+				Ok(response) => {
+					println!("Signature valid: {}", response.valid);
+				},
+				Err(e) => {
+					eprintln!("Error verifying signature: {:?}", e);
+				},
+			};
+		},
+		Commands::NewAddress => {
 			match client.get_new_funding_address(OnchainReceiveRequest {}).await {
 				Ok(address) => {
 					println!("New address: {:?}", address);
@@ -104,8 +401,17 @@ async fn main() {
 				},
 			};
 		},
-		Commands::SendOnchain { address, amount_sats } => {
-			match client.send_onchain(OnchainSendRequest { address, amount_sats }).await {
+		Commands::SendOnchain {
+			address,
+			amount_sats,
+			fee_rate_sat_per_vb,
+			confirmation_target_blocks,
+		} => {
+			let fee_rate = fee_rate_sat_per_vb
+				.map(onchain_send_request::FeeRate::FeeRateSatPerVb)
+				.or(confirmation_target_blocks
+					.map(onchain_send_request::FeeRate::ConfirmationTargetBlocks));
+			match client.send_onchain(OnchainSendRequest { address, amount_sats, fee_rate }).await {
 				Ok(response) => {
 					println!("Sent onchain: {:?}", response);
 				},
@@ -114,7 +420,17 @@ async fn main() {
 				},
 			};
 		},
-		Commands::NodeBalances {} => {
+		Commands::OnchainTransactions => {
+			match client.list_onchain_transactions(ListOnchainTransactionsRequest {}).await {
+				Ok(response) => {
+					println!("On-chain transactions: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing on-chain transactions: {:?}", e);
+				},
+			};
+		},
+		Commands::NodeBalances => {
 			match client.get_node_balances(GetBalancesRequest {}).await {
 				Ok(response) => {
 					println!("Node balances: {:?}", response);
@@ -124,7 +440,17 @@ async fn main() {
 				},
 			};
 		},
-		Commands::ListChannels {} => {
+		Commands::FeeEstimates => {
+			match client.get_fee_estimates(GetFeeEstimatesRequest {}).await {
+				Ok(response) => {
+					println!("Fee estimates: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting fee estimates: {:?}", e);
+				},
+			};
+		},
+		Commands::ListChannels => {
 			match client.list_channels(ListChannelsRequest {}).await {
 				Ok(response) => {
 					println!("Channels: {:?}", response);
@@ -134,8 +460,69 @@ async fn main() {
 				},
 			};
 		},
-		Commands::PaymentsHistory => {
-			match client.get_payments_history(&PaymentsHistoryRequest {}).await {
+		Commands::PendingApprovals => {
+			match client.list_pending_approvals(ListPendingApprovalsRequest {}).await {
+				Ok(response) => {
+					println!("Pending approvals: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting pending approvals: {:?}", e);
+				},
+			};
+		},
+		Commands::ApprovePayment { id, approval_token } => {
+			match client.approve_payment(ApprovePaymentRequest { id, approval_token }).await {
+				Ok(response) => {
+					println!("Approved payment: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error approving payment: {:?}", e);
+				},
+			};
+		},
+		Commands::RejectPayment { id, approval_token } => {
+			match client.reject_payment(RejectPaymentRequest { id, approval_token }).await {
+				Ok(response) => {
+					println!("Rejected payment: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error rejecting payment: {:?}", e);
+				},
+			};
+		},
+		Commands::AuditLog { offset, limit } => {
+			match client.list_audit_log(ListAuditLogRequest { offset, limit }).await {
+				Ok(response) => {
+					println!("Audit log: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting audit log: {:?}", e);
+				},
+			};
+		},
+		Commands::VerifyAuditLog => {
+			match client.verify_audit_log(VerifyAuditLogRequest {}).await {
+				Ok(response) => {
+					println!("Audit log verification: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error verifying audit log: {:?}", e);
+				},
+			};
+		},
+		Commands::ListClosedChannels => {
+			match client.list_closed_channels(ListClosedChannelsRequest {}).await {
+				Ok(response) => {
+					println!("Closed channels: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting list of closed channels: {:?}", e);
+				},
+			};
+		},
+		Commands::PaymentsHistory { label, external_id } => {
+			match client.get_payments_history(&PaymentsHistoryRequest { label, external_id }).await
+			{
 				Ok(response) => {
 					println!("Payments history: {:?}", response);
 				},
@@ -154,9 +541,64 @@ async fn main() {
 				},
 			};
 		},
-		Commands::Bolt11Receive { description, expiry_secs, amount_msat } => {
+		Commands::PaymentsByHash { payment_hash } => {
+			match client.get_payments_by_hash(GetPaymentsByHashRequest { payment_hash }).await {
+				Ok(response) => {
+					println!("Payments: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting payments: {:?}", e);
+				},
+			};
+		},
+		Commands::ListForwards { start, end, offset, limit } => {
 			match client
-				.bolt11_receive(Bolt11ReceiveRequest { description, expiry_secs, amount_msat })
+				.list_forwards(ListForwardsRequest {
+					start_timestamp: start,
+					end_timestamp: end,
+					offset,
+					limit,
+				})
+				.await
+			{
+				Ok(response) => {
+					println!("Forwards: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing forwards: {:?}", e);
+				},
+			};
+		},
+		Commands::Export { start, end, format, output } => {
+			match client
+				.export(ExportRequest {
+					start_timestamp: start,
+					end_timestamp: end,
+					format: format.into(),
+				})
+				.await
+			{
+				Ok(response) => match output {
+					Some(path) => match fs::write(&path, response.data) {
+						Ok(()) => println!("Exported to {}", path.display()),
+						Err(e) => eprintln!("Error writing export to {}: {}", path.display(), e),
+					},
+					None => print!("{}", response.data),
+				},
+				Err(e) => {
+					eprintln!("Error exporting: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Receive { description, expiry_secs, amount_msat, metadata } => {
+			let metadata = metadata.into_proto();
+			match client
+				.bolt11_receive(Bolt11ReceiveRequest {
+					description,
+					expiry_secs,
+					amount_msat,
+					metadata,
+				})
 				.await
 			{
 				Ok(response) => {
@@ -167,8 +609,18 @@ async fn main() {
 				},
 			};
 		},
-		Commands::Bolt11Send { invoice, amount_msat } => {
-			match client.bolt11_send(Bolt11SendRequest { invoice, amount_msat }).await {
+		Commands::Bolt11Send { invoice, amount_msat, sending_parameters, metadata } => {
+			let sending_parameters = sending_parameters.into_proto();
+			let metadata = metadata.into_proto();
+			match client
+				.bolt11_send(Bolt11SendRequest {
+					invoice,
+					amount_msat,
+					sending_parameters,
+					metadata,
+				})
+				.await
+			{
 				Ok(response) => {
 					println!("Sent BOLT11 payment: {:?}", response);
 				},
@@ -177,13 +629,199 @@ async fn main() {
 				},
 			};
 		},
+		Commands::Bolt11ReceiveForHash {
+			description,
+			expiry_secs,
+			payment_hash,
+			amount_msat,
+			metadata,
+		} => {
+			let metadata = metadata.into_proto();
+			match client
+				.bolt11_receive_for_hash(Bolt11ReceiveForHashRequest {
+					description,
+					expiry_secs,
+					amount_msat,
+					payment_hash,
+					metadata,
+				})
+				.await
+			{
+				Ok(response) => {
+					println!("New hold invoice: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting hold invoice: {:?}", e);
+				},
+			};
+		},
+		Commands::ClaimablePayments => {
+			match client.list_claimable_payments(ListClaimablePaymentsRequest {}).await {
+				Ok(response) => {
+					println!("Claimable payments: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing claimable payments: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Claim { payment_hash, preimage, claimable_amount_msat } => {
+			match client
+				.bolt11_claim(Bolt11ClaimRequest { payment_hash, preimage, claimable_amount_msat })
+				.await
+			{
+				Ok(_) => {
+					println!("Claimed payment");
+				},
+				Err(e) => {
+					eprintln!("Error claiming payment: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Fail { payment_hash } => {
+			match client.bolt11_fail(Bolt11FailRequest { payment_hash }).await {
+				Ok(_) => {
+					println!("Failed payment");
+				},
+				Err(e) => {
+					eprintln!("Error failing payment: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Decode { invoice } => {
+			match client.bolt11_decode(Bolt11DecodeRequest { invoice }).await {
+				Ok(response) => {
+					println!("Decoded invoice: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error decoding invoice: {:?}", e);
+				},
+			};
+		},
+		Commands::ListInvoices { status } => {
+			match client.list_invoices(ListInvoicesRequest { status: status.map(Into::into) }).await
+			{
+				Ok(response) => {
+					println!("Invoices: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing invoices: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Cancel { payment_hash } => {
+			match client.bolt11_cancel(Bolt11CancelRequest { payment_hash }).await {
+				Ok(_) => {
+					println!("Cancelled invoice");
+				},
+				Err(e) => {
+					eprintln!("Error cancelling invoice: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt12Send { offer, amount_msat, payer_note, metadata } => {
+			let metadata = metadata.into_proto();
+			match client
+				.bolt12_send(Bolt12SendRequest { offer, amount_msat, payer_note, metadata })
+				.await
+			{
+				Ok(response) => {
+					println!("Sent BOLT12 payment: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error sending BOLT12 payment: {:?}", e);
+				},
+			};
+		},
+		Commands::SpontaneousSend { node_id, amount_msat, sending_parameters, metadata } => {
+			let sending_parameters = sending_parameters.into_proto();
+			let metadata = metadata.into_proto();
+			match client
+				.spontaneous_send(SpontaneousSendRequest {
+					node_id,
+					amount_msat,
+					sending_parameters,
+					metadata,
+				})
+				.await
+			{
+				Ok(response) => {
+					println!("Sent spontaneous payment: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error sending spontaneous payment: {:?}", e);
+				},
+			};
+		},
+		Commands::UnifiedReceive { amount_sats, description, expiry_secs, qr } => {
+			match client
+				.unified_receive(UnifiedReceiveRequest { amount_sats, description, expiry_secs })
+				.await
+			{
+				Ok(response) => {
+					println!("Unified URI: {}", response.uri);
+					if qr {
+						match QrCode::new(response.uri.as_bytes()) {
+							Ok(code) => println!("{}", code.render::<Dense1x2>().build()),
+							Err(e) => eprintln!("Error rendering QR code: {:?}", e),
+						}
+					}
+				},
+				Err(e) => {
+					eprintln!("Error getting unified URI: {:?}", e);
+				},
+			};
+		},
+		Commands::UnifiedSend { uri } => {
+			match client.unified_send(UnifiedSendRequest { uri }).await {
+				Ok(response) => {
+					println!("Sent unified payment: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error sending unified payment: {:?}", e);
+				},
+			};
+		},
+		Commands::Bolt11Probe { invoice, amount_msat } => {
+			match client.bolt11_probe(Bolt11ProbeRequest { invoice, amount_msat }).await {
+				Ok(response) => {
+					println!("Probed BOLT11 invoice: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error probing BOLT11 invoice: {:?}", e);
+				},
+			};
+		},
+		Commands::SpontaneousProbe { node_id, amount_msat } => {
+			match client.spontaneous_probe(SpontaneousProbeRequest { node_id, amount_msat }).await {
+				Ok(response) => {
+					println!("Probed node: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error probing node: {:?}", e);
+				},
+			};
+		},
 		Commands::OpenChannel {
 			node_id,
 			address,
 			channel_amount_sats,
 			push_to_counterparty_msat,
 			announce_channel,
+			forwarding_fee_proportional_millionths,
+			forwarding_fee_base_msat,
+			cltv_expiry_delta,
+			max_dust_htlc_exposure_msat,
 		} => {
+			let channel_config = ChannelConfig {
+				forwarding_fee_proportional_millionths,
+				forwarding_fee_base_msat,
+				cltv_expiry_delta,
+				max_dust_htlc_exposure: max_dust_htlc_exposure_msat
+					.map(channel_config::MaxDustHtlcExposure::FixedLimitMsat),
+				force_close_avoidance_max_fee_satoshis: None,
+				accept_underpaying_htlcs: None,
+			};
 			match client
 				.open_channel(OpenChannelRequest {
 					node_id,
@@ -191,6 +829,7 @@ async fn main() {
 					channel_amount_sats,
 					push_to_counterparty_msat,
 					announce_channel,
+					channel_config: Some(channel_config),
 				})
 				.await
 			{
@@ -202,12 +841,16 @@ async fn main() {
 				},
 			};
 		},
-		Commands::CloseChannel { user_channel_id, counterparty_node_id } => {
+		Commands::CloseChannel {
+			user_channel_id,
+			channel_id,
+			funding_txo,
+			counterparty_node_id,
+			all_channels,
+		} => {
+			let channel = channel_identifier(user_channel_id, channel_id, funding_txo);
 			match client
-				.close_channel(CloseChannelRequest {
-					user_channel_id: user_channel_id.into_bytes(),
-					counterparty_node_id,
-				})
+				.close_channel(CloseChannelRequest { channel, counterparty_node_id, all_channels })
 				.await
 			{
 				Ok(response) => {
@@ -218,11 +861,19 @@ async fn main() {
 				},
 			};
 		},
-		Commands::ForceCloseChannel { user_channel_id, counterparty_node_id } => {
+		Commands::ForceCloseChannel {
+			user_channel_id,
+			channel_id,
+			funding_txo,
+			counterparty_node_id,
+			all_channels,
+		} => {
+			let channel = channel_identifier(user_channel_id, channel_id, funding_txo);
 			match client
 				.force_close_channel(ForceCloseChannelRequest {
-					user_channel_id: user_channel_id.into_bytes(),
+					channel,
 					counterparty_node_id,
+					all_channels,
 				})
 				.await
 			{
@@ -234,5 +885,149 @@ async fn main() {
 				},
 			};
 		},
+		Commands::UpdateChannelConfig {
+			user_channel_id,
+			counterparty_node_id,
+			forwarding_fee_proportional_millionths,
+			forwarding_fee_base_msat,
+			cltv_expiry_delta,
+			max_dust_htlc_exposure_msat,
+			max_dust_htlc_exposure_fee_rate_multiplier,
+			force_close_avoidance_max_fee_satoshis,
+			accept_underpaying_htlcs,
+		} => {
+			let max_dust_htlc_exposure = max_dust_htlc_exposure_msat
+				.map(channel_config::MaxDustHtlcExposure::FixedLimitMsat)
+				.or(max_dust_htlc_exposure_fee_rate_multiplier
+					.map(channel_config::MaxDustHtlcExposure::FeeRateMultiplier));
+			let channel_config = ChannelConfig {
+				forwarding_fee_proportional_millionths,
+				forwarding_fee_base_msat,
+				cltv_expiry_delta,
+				max_dust_htlc_exposure,
+				force_close_avoidance_max_fee_satoshis,
+				accept_underpaying_htlcs,
+			};
+			match client
+				.update_channel_config(UpdateChannelConfigRequest {
+					user_channel_id,
+					counterparty_node_id,
+					channel_config: Some(channel_config),
+				})
+				.await
+			{
+				Ok(response) => {
+					println!("Update channel config response: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error updating channel config: {:?}", e);
+				},
+			};
+		},
+		Commands::GraphChannels { offset, limit } => {
+			match client.list_graph_channels(ListGraphChannelsRequest { offset, limit }).await {
+				Ok(response) => {
+					println!("Graph channels: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing graph channels: {:?}", e);
+				},
+			};
+		},
+		Commands::GraphChannel { short_channel_id } => {
+			match client.get_graph_channel(GetGraphChannelRequest { short_channel_id }).await {
+				Ok(response) => {
+					println!("Graph channel: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting graph channel: {:?}", e);
+				},
+			};
+		},
+		Commands::GraphNodes { offset, limit } => {
+			match client.list_graph_nodes(ListGraphNodesRequest { offset, limit }).await {
+				Ok(response) => {
+					println!("Graph nodes: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error listing graph nodes: {:?}", e);
+				},
+			};
+		},
+		Commands::GraphNode { node_id } => {
+			match client.get_graph_node(GetGraphNodeRequest { node_id }).await {
+				Ok(response) => {
+					println!("Graph node: {:?}", response);
+				},
+				Err(e) => {
+					eprintln!("Error getting graph node: {:?}", e);
+				},
+			};
+		},
+	}
+}
+
+fn channel_identifier(
+	user_channel_id: Option<String>, channel_id: Option<String>, funding_txo: Option<Outpoint>,
+) -> Option<ChannelIdentifier> {
+	let id = if let Some(user_channel_id) = user_channel_id {
+		channel_identifier::Id::UserChannelId(user_channel_id)
+	} else if let Some(channel_id) = channel_id {
+		channel_identifier::Id::ChannelId(channel_id)
+	} else {
+		channel_identifier::Id::FundingTxo(funding_txo?)
+	};
+	Some(ChannelIdentifier { id: Some(id) })
+}
+
+fn parse_outpoint(outpoint: &str) -> Result<Outpoint, String> {
+	let (txid, vout) =
+		outpoint.split_once(':').ok_or_else(|| "expected format TXID:VOUT".to_string())?;
+	let vout = vout.parse().map_err(|_| format!("invalid output index: {}", vout))?;
+	Ok(Outpoint { txid: txid.to_string(), vout })
+}
+
+fn parse_invoice_status(status: &str) -> Result<InvoiceStatus, String> {
+	InvoiceStatus::from_str_name(&status.to_uppercase())
+		.ok_or_else(|| format!("invalid invoice status: {}", status))
+}
+
+fn parse_export_format(format: &str) -> Result<ExportFormat, String> {
+	match format {
+		"csv" => Ok(ExportFormat::Csv),
+		"jsonl" => Ok(ExportFormat::JsonLines),
+		_ => Err(format!("invalid export format: {}", format)),
 	}
 }
+
+/// Parses either a unix timestamp or a UTC date in the form YYYY-MM-DD, which is converted to the
+/// timestamp of its midnight.
+fn parse_timestamp(timestamp: &str) -> Result<u64, String> {
+	if let Ok(timestamp) = timestamp.parse::<u64>() {
+		return Ok(timestamp);
+	}
+	let invalid =
+		|| format!("expected a unix timestamp or a date in the form YYYY-MM-DD: {}", timestamp);
+	let parts = timestamp
+		.split('-')
+		.map(|part| part.parse::<u64>())
+		.collect::<Result<Vec<_>, _>>()
+		.map_err(|_| invalid())?;
+	let (year, month, day) = match parts[..] {
+		[year, month, day]
+			if year >= 1970 && (1..=12).contains(&month) && (1..=31).contains(&day) =>
+		{
+			(year, month, day)
+		},
+		_ => return Err(invalid()),
+	};
+	// Count the days since the unix epoch with years starting in March, so that leap days fall at
+	// the end of the year. See http://howardhinnant.github.io/date_algorithms.html#days_from_civil.
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year / 400;
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	let days = era * 146097 + day_of_era - 719468;
+	Ok(days * 86400)
+}
@@ -17,3 +17,9 @@ impl From<reqwest::Error> for ServerHackError {
 		ServerHackError::InternalError(err.to_string())
 	}
 }
+
+impl From<getrandom::Error> for ServerHackError {
+	fn from(err: getrandom::Error) -> Self {
+		ServerHackError::InternalError(err.to_string())
+	}
+}
@@ -4,32 +4,89 @@ use crate::error::ServerHackError;
 use prost::Message;
 
 use protos::{
-	Bolt11ReceiveRequest, Bolt11ReceiveResponse, Bolt11SendRequest, Bolt11SendResponse,
-	CloseChannelRequest, CloseChannelResponse, ForceCloseChannelRequest, ForceCloseChannelResponse,
-	GetBalancesRequest, GetBalancesResponse, GetNodeIdRequest, GetNodeIdResponse,
-	GetNodeStatusRequest, GetNodeStatusResponse, GetPaymentDetailsRequest,
-	GetPaymentDetailsResponse, ListChannelsRequest, ListChannelsResponse, OnchainReceiveRequest,
-	OnchainReceiveResponse, OnchainSendRequest, OnchainSendResponse, OpenChannelRequest,
-	OpenChannelResponse, PaymentsHistoryRequest, PaymentsHistoryResponse,
+	ApprovePaymentRequest, ApprovePaymentResponse, Bolt11CancelRequest, Bolt11CancelResponse,
+	Bolt11ClaimRequest, Bolt11ClaimResponse, Bolt11DecodeRequest, Bolt11DecodeResponse,
+	Bolt11FailRequest, Bolt11FailResponse, Bolt11ProbeRequest, Bolt11ProbeResponse,
+	Bolt11ReceiveForHashRequest, Bolt11ReceiveForHashResponse, Bolt11ReceiveRequest,
+	Bolt11ReceiveResponse, Bolt11SendRequest, Bolt11SendResponse, Bolt12SendRequest,
+	Bolt12SendResponse, CloseChannelRequest, CloseChannelResponse, ExportRequest, ExportResponse,
+	ForceCloseChannelRequest, ForceCloseChannelResponse, GetBalancesRequest, GetBalancesResponse,
+	GetFeeEstimatesRequest, GetFeeEstimatesResponse, GetGraphChannelRequest,
+	GetGraphChannelResponse, GetGraphNodeRequest, GetGraphNodeResponse, GetNodeIdRequest,
+	GetNodeIdResponse, GetNodeStatusRequest, GetNodeStatusResponse, GetPaymentDetailsRequest,
+	GetPaymentDetailsResponse, GetPaymentsByHashRequest, GetPaymentsByHashResponse,
+	ListAuditLogRequest, ListAuditLogResponse, ListChannelsRequest, ListChannelsResponse,
+	ListClaimablePaymentsRequest, ListClaimablePaymentsResponse, ListClosedChannelsRequest,
+	ListClosedChannelsResponse, ListForwardsRequest, ListForwardsResponse,
+	ListGraphChannelsRequest, ListGraphChannelsResponse, ListGraphNodesRequest,
+	ListGraphNodesResponse, ListInvoicesRequest, ListInvoicesResponse,
+	ListOnchainTransactionsRequest, ListOnchainTransactionsResponse, ListPendingApprovalsRequest,
+	ListPendingApprovalsResponse, OnchainReceiveRequest, OnchainReceiveResponse,
+	OnchainSendRequest, OnchainSendResponse, OpenChannelRequest, OpenChannelResponse,
+	PaymentsHistoryRequest, PaymentsHistoryResponse, RejectPaymentRequest, RejectPaymentResponse,
+	SignMessageRequest, SignMessageResponse, SpontaneousProbeRequest, SpontaneousProbeResponse,
+	SpontaneousSendRequest, SpontaneousSendResponse, UnifiedReceiveRequest, UnifiedReceiveResponse,
+	UnifiedSendRequest, UnifiedSendResponse, UpdateChannelConfigRequest,
+	UpdateChannelConfigResponse, VerifyAuditLogRequest, VerifyAuditLogResponse,
+	VerifySignatureRequest, VerifySignatureResponse,
 };
 use reqwest::header::CONTENT_TYPE;
 use reqwest::Client;
 
+use std::time::Duration;
+
 const APPLICATION_OCTET_STREAM: &str = "application/octet-stream";
 
 const GET_NODE_ID_PATH: &str = "getNodeId";
 const GET_NODE_STATUS_PATH: &str = "getNodeStatus";
+const SIGN_MESSAGE_PATH: &str = "sign-message";
+const VERIFY_SIGNATURE_PATH: &str = "verify-signature";
+const LIST_GRAPH_CHANNELS_PATH: &str = "graph/channels";
+const GET_GRAPH_CHANNEL_PATH: &str = "graph/channel";
+const LIST_GRAPH_NODES_PATH: &str = "graph/nodes";
+const GET_GRAPH_NODE_PATH: &str = "graph/node";
 const ONCHAIN_RECEIVE_PATH: &str = "onchain/receive";
 const ONCHAIN_SEND_PATH: &str = "onchain/send";
+const ONCHAIN_TRANSACTIONS_PATH: &str = "onchain/transactions";
 const BOLT11_RECEIVE_PATH: &str = "bolt11/receive";
 const BOLT11_SEND_PATH: &str = "bolt11/send";
+const BOLT11_DECODE_PATH: &str = "bolt11/decode";
+const BOLT11_LIST_INVOICES_PATH: &str = "bolt11/invoices";
+const BOLT11_CANCEL_PATH: &str = "bolt11/cancel";
+const BOLT11_RECEIVE_FOR_HASH_PATH: &str = "bolt11/receive-for-hash";
+const BOLT11_CLAIMABLE_PATH: &str = "bolt11/claimable";
+const BOLT11_CLAIM_PATH: &str = "bolt11/claim";
+const BOLT11_FAIL_PATH: &str = "bolt11/fail";
+const BOLT11_PROBE_PATH: &str = "bolt11/probe";
+const BOLT12_SEND_PATH: &str = "bolt12/send";
+const SPONTANEOUS_SEND_PATH: &str = "spontaneous/send";
+const UNIFIED_RECEIVE_PATH: &str = "unified/receive";
+const UNIFIED_SEND_PATH: &str = "unified/send";
+const SPONTANEOUS_PROBE_PATH: &str = "spontaneous/probe";
 const GET_NODE_BALANCES_PATH: &str = "getNodeBalances";
+const GET_FEE_ESTIMATES_PATH: &str = "getFeeEstimates";
 const PAYMENTS_HISTORY_PATH: &str = "listPaymentsHistory";
 const GET_PAYMENT_DETAILS_PATH: &str = "getPaymentDetails";
+const GET_PAYMENTS_BY_HASH_PATH: &str = "payment/by-hash";
 const LIST_CHANNELS_PATH: &str = "channel/list";
+const LIST_CLOSED_CHANNELS_PATH: &str = "channel/closed";
 const OPEN_CHANNEL_PATH: &str = "channel/open";
 const CLOSE_CHANNEL_PATH: &str = "channel/close";
 const FORCE_CLOSE_CHANNEL_PATH: &str = "channel/force-close";
+const UPDATE_CHANNEL_CONFIG_PATH: &str = "channel/update-config";
+const EXPORT_PATH: &str = "export";
+const LIST_FORWARDS_PATH: &str = "forwards/list";
+const LIST_PENDING_APPROVALS_PATH: &str = "policy/pending";
+const APPROVE_PAYMENT_PATH: &str = "policy/approve";
+const REJECT_PAYMENT_PATH: &str = "policy/reject";
+const LIST_AUDIT_LOG_PATH: &str = "audit/list";
+const VERIFY_AUDIT_LOG_PATH: &str = "audit/verify";
+
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+/// How often a request carrying an idempotency key is attempted before giving up.
+const MAX_IDEMPOTENT_REQUEST_ATTEMPTS: u32 = 5;
+/// The delay before retrying a request for the first time, which doubles with every retry.
+const IDEMPOTENT_REQUEST_INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 pub struct ServerHackClient {
@@ -56,6 +113,48 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	pub async fn sign_message(
+		&self, request: SignMessageRequest,
+	) -> Result<SignMessageResponse, ServerHackError> {
+		let url = format!("http://{}/{SIGN_MESSAGE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn verify_signature(
+		&self, request: VerifySignatureRequest,
+	) -> Result<VerifySignatureResponse, ServerHackError> {
+		let url = format!("http://{}/{VERIFY_SIGNATURE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_graph_channels(
+		&self, request: ListGraphChannelsRequest,
+	) -> Result<ListGraphChannelsResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_GRAPH_CHANNELS_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn get_graph_channel(
+		&self, request: GetGraphChannelRequest,
+	) -> Result<GetGraphChannelResponse, ServerHackError> {
+		let url = format!("http://{}/{GET_GRAPH_CHANNEL_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_graph_nodes(
+		&self, request: ListGraphNodesRequest,
+	) -> Result<ListGraphNodesResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_GRAPH_NODES_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn get_graph_node(
+		&self, request: GetGraphNodeRequest,
+	) -> Result<GetGraphNodeResponse, ServerHackError> {
+		let url = format!("http://{}/{GET_GRAPH_NODE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
 	pub async fn get_new_funding_address(
 		&self, request: OnchainReceiveRequest,
 	) -> Result<OnchainReceiveResponse, ServerHackError> {
@@ -63,10 +162,26 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	/// Sends the payment under a freshly generated idempotency key, see
+	/// [`Self::send_onchain_with_idempotency_key`].
 	pub async fn send_onchain(
 		&self, request: OnchainSendRequest,
+	) -> Result<OnchainSendResponse, ServerHackError> {
+		self.send_onchain_with_idempotency_key(request, &generate_idempotency_key()?).await
+	}
+
+	/// Sends the payment, which the server executes at most once per idempotency key.
+	pub async fn send_onchain_with_idempotency_key(
+		&self, request: OnchainSendRequest, idempotency_key: &str,
 	) -> Result<OnchainSendResponse, ServerHackError> {
 		let url = format!("http://{}/{ONCHAIN_SEND_PATH}", self.base_url);
+		self.post_idempotent_request(&request, &url, idempotency_key).await
+	}
+
+	pub async fn list_onchain_transactions(
+		&self, request: ListOnchainTransactionsRequest,
+	) -> Result<ListOnchainTransactionsResponse, ServerHackError> {
+		let url = format!("http://{}/{ONCHAIN_TRANSACTIONS_PATH}", self.base_url);
 		self.post_request(&request, &url).await
 	}
 
@@ -77,10 +192,110 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	/// Sends the payment under a freshly generated idempotency key, see
+	/// [`Self::bolt11_send_with_idempotency_key`].
 	pub async fn bolt11_send(
 		&self, request: Bolt11SendRequest,
+	) -> Result<Bolt11SendResponse, ServerHackError> {
+		self.bolt11_send_with_idempotency_key(request, &generate_idempotency_key()?).await
+	}
+
+	/// Sends the payment, which the server executes at most once per idempotency key.
+	pub async fn bolt11_send_with_idempotency_key(
+		&self, request: Bolt11SendRequest, idempotency_key: &str,
 	) -> Result<Bolt11SendResponse, ServerHackError> {
 		let url = format!("http://{}/{BOLT11_SEND_PATH}", self.base_url);
+		self.post_idempotent_request(&request, &url, idempotency_key).await
+	}
+
+	pub async fn bolt11_receive_for_hash(
+		&self, request: Bolt11ReceiveForHashRequest,
+	) -> Result<Bolt11ReceiveForHashResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_RECEIVE_FOR_HASH_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_claimable_payments(
+		&self, request: ListClaimablePaymentsRequest,
+	) -> Result<ListClaimablePaymentsResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_CLAIMABLE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt11_claim(
+		&self, request: Bolt11ClaimRequest,
+	) -> Result<Bolt11ClaimResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_CLAIM_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt11_fail(
+		&self, request: Bolt11FailRequest,
+	) -> Result<Bolt11FailResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_FAIL_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt11_decode(
+		&self, request: Bolt11DecodeRequest,
+	) -> Result<Bolt11DecodeResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_DECODE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_invoices(
+		&self, request: ListInvoicesRequest,
+	) -> Result<ListInvoicesResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_LIST_INVOICES_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt11_cancel(
+		&self, request: Bolt11CancelRequest,
+	) -> Result<Bolt11CancelResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_CANCEL_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt12_send(
+		&self, request: Bolt12SendRequest,
+	) -> Result<Bolt12SendResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT12_SEND_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn spontaneous_send(
+		&self, request: SpontaneousSendRequest,
+	) -> Result<SpontaneousSendResponse, ServerHackError> {
+		let url = format!("http://{}/{SPONTANEOUS_SEND_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn unified_receive(
+		&self, request: UnifiedReceiveRequest,
+	) -> Result<UnifiedReceiveResponse, ServerHackError> {
+		let url = format!("http://{}/{UNIFIED_RECEIVE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn unified_send(
+		&self, request: UnifiedSendRequest,
+	) -> Result<UnifiedSendResponse, ServerHackError> {
+		let url = format!("http://{}/{UNIFIED_SEND_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn bolt11_probe(
+		&self, request: Bolt11ProbeRequest,
+	) -> Result<Bolt11ProbeResponse, ServerHackError> {
+		let url = format!("http://{}/{BOLT11_PROBE_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn spontaneous_probe(
+		&self, request: SpontaneousProbeRequest,
+	) -> Result<SpontaneousProbeResponse, ServerHackError> {
+		let url = format!("http://{}/{SPONTANEOUS_PROBE_PATH}", self.base_url);
 		self.post_request(&request, &url).await
 	}
 
@@ -91,6 +306,13 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	pub async fn get_fee_estimates(
+		&self, request: GetFeeEstimatesRequest,
+	) -> Result<GetFeeEstimatesResponse, ServerHackError> {
+		let url = format!("http://{}/{GET_FEE_ESTIMATES_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
 	pub async fn list_channels(
 		&self, request: ListChannelsRequest,
 	) -> Result<ListChannelsResponse, ServerHackError> {
@@ -98,6 +320,13 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	pub async fn list_closed_channels(
+		&self, request: ListClosedChannelsRequest,
+	) -> Result<ListClosedChannelsResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_CLOSED_CHANNELS_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
 	pub async fn get_payments_history(
 		&self, request: &PaymentsHistoryRequest,
 	) -> Result<PaymentsHistoryResponse, ServerHackError> {
@@ -112,11 +341,74 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	pub async fn get_payments_by_hash(
+		&self, request: GetPaymentsByHashRequest,
+	) -> Result<GetPaymentsByHashResponse, ServerHackError> {
+		let url = format!("http://{}/{GET_PAYMENTS_BY_HASH_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_forwards(
+		&self, request: ListForwardsRequest,
+	) -> Result<ListForwardsResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_FORWARDS_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn export(&self, request: ExportRequest) -> Result<ExportResponse, ServerHackError> {
+		let url = format!("http://{}/{EXPORT_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_pending_approvals(
+		&self, request: ListPendingApprovalsRequest,
+	) -> Result<ListPendingApprovalsResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_PENDING_APPROVALS_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn approve_payment(
+		&self, request: ApprovePaymentRequest,
+	) -> Result<ApprovePaymentResponse, ServerHackError> {
+		let url = format!("http://{}/{APPROVE_PAYMENT_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn reject_payment(
+		&self, request: RejectPaymentRequest,
+	) -> Result<RejectPaymentResponse, ServerHackError> {
+		let url = format!("http://{}/{REJECT_PAYMENT_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn list_audit_log(
+		&self, request: ListAuditLogRequest,
+	) -> Result<ListAuditLogResponse, ServerHackError> {
+		let url = format!("http://{}/{LIST_AUDIT_LOG_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	pub async fn verify_audit_log(
+		&self, request: VerifyAuditLogRequest,
+	) -> Result<VerifyAuditLogResponse, ServerHackError> {
+		let url = format!("http://{}/{VERIFY_AUDIT_LOG_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
+	/// Opens the channel under a freshly generated idempotency key, see
+	/// [`Self::open_channel_with_idempotency_key`].
 	pub async fn open_channel(
 		&self, request: OpenChannelRequest,
+	) -> Result<OpenChannelResponse, ServerHackError> {
+		self.open_channel_with_idempotency_key(request, &generate_idempotency_key()?).await
+	}
+
+	/// Opens the channel, which the server does at most once per idempotency key.
+	pub async fn open_channel_with_idempotency_key(
+		&self, request: OpenChannelRequest, idempotency_key: &str,
 	) -> Result<OpenChannelResponse, ServerHackError> {
 		let url = format!("http://{}/{OPEN_CHANNEL_PATH}", self.base_url);
-		self.post_request(&request, &url).await
+		self.post_idempotent_request(&request, &url, idempotency_key).await
 	}
 
 	pub async fn close_channel(
@@ -133,18 +425,51 @@ impl ServerHackClient {
 		self.post_request(&request, &url).await
 	}
 
+	pub async fn update_channel_config(
+		&self, request: UpdateChannelConfigRequest,
+	) -> Result<UpdateChannelConfigResponse, ServerHackError> {
+		let url = format!("http://{}/{UPDATE_CHANNEL_CONFIG_PATH}", self.base_url);
+		self.post_request(&request, &url).await
+	}
+
 	async fn post_request<Rq: Message, Rs: Message + Default>(
 		&self, request: &Rq, url: &str,
+	) -> Result<Rs, ServerHackError> {
+		self.send_request(request, url, None).await
+	}
+
+	/// Posts the request, retrying it if it may not have reached the server or is still being
+	/// handled by it. This is safe as the server executes it at most once for the given key.
+	async fn post_idempotent_request<Rq: Message, Rs: Message + Default>(
+		&self, request: &Rq, url: &str, idempotency_key: &str,
+	) -> Result<Rs, ServerHackError> {
+		let mut attempts = 1;
+		let mut retry_delay = IDEMPOTENT_REQUEST_INITIAL_RETRY_DELAY;
+		loop {
+			match self.send_request(request, url, Some(idempotency_key)).await {
+				Err(ServerHackError::InternalError(_))
+				| Err(ServerHackError::FailedRequest(reqwest::StatusCode::CONFLICT, _))
+					if attempts < MAX_IDEMPOTENT_REQUEST_ATTEMPTS =>
+				{
+					attempts += 1;
+					tokio::time::sleep(retry_delay).await;
+					retry_delay *= 2;
+				},
+				result => return result,
+			}
+		}
+	}
+
+	async fn send_request<Rq: Message, Rs: Message + Default>(
+		&self, request: &Rq, url: &str, idempotency_key: Option<&str>,
 	) -> Result<Rs, ServerHackError> {
 		let request_body = request.encode_to_vec();
-		let response_raw = match self
-			.client
-			.post(url)
-			.header(CONTENT_TYPE, APPLICATION_OCTET_STREAM)
-			.body(request_body.clone())
-			.send()
-			.await
-		{
+		let mut request_builder =
+			self.client.post(url).header(CONTENT_TYPE, APPLICATION_OCTET_STREAM);
+		if let Some(idempotency_key) = idempotency_key {
+			request_builder = request_builder.header(IDEMPOTENCY_KEY_HEADER, idempotency_key);
+		}
+		let response_raw = match request_builder.body(request_body.clone()).send().await {
 			Ok(response) => response,
 			Err(e) => {
 				return Err(ServerHackError::InternalError(e.to_string()));
@@ -168,3 +493,10 @@ impl ServerHackClient {
 		}
 	}
 }
+
+/// Generates a random 128-bit key, which is unique to this request with overwhelming probability.
+fn generate_idempotency_key() -> Result<String, ServerHackError> {
+	let mut key = [0u8; 16];
+	getrandom::getrandom(&mut key)?;
+	Ok(key.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
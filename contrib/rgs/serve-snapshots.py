@@ -0,0 +1,51 @@
+#!/usr/bin/env python3
+"""Serves Rapid Gossip Sync snapshots over HTTP for local regtest setups.
+
+The snapshots in the given directory are copied to a scratch directory and served from there. If
+no snapshot named `0`, the one requested on initial sync, is present, an empty regtest snapshot is
+generated in its place.
+
+Usage: serve-snapshots.py <snapshot-dir> [port]
+"""
+
+import functools
+import http.server
+import os
+import shutil
+import struct
+import sys
+import tempfile
+import time
+
+# The genesis block hash of regtest, in the byte order used by LDK.
+REGTEST_CHAIN_HASH = bytes.fromhex(
+    "06226e46111a0b59caaf126043eb5bbf28c34f3a5e332a1fc7b2b73cf188910f"
+)
+
+
+def empty_snapshot():
+    # The version 1 prefix, followed by the chain hash, the timestamp of the snapshot and the
+    # counts of node ids, channel announcements and channel updates.
+    return b"LDK\x01" + REGTEST_CHAIN_HASH + struct.pack(">IIII", int(time.time()), 0, 0, 0)
+
+
+def main():
+    if len(sys.argv) not in (2, 3):
+        sys.exit(__doc__.strip().splitlines()[-1])
+    snapshot_dir = sys.argv[1]
+    port = int(sys.argv[2]) if len(sys.argv) == 3 else 8000
+
+    serve_dir = tempfile.mkdtemp(prefix="rgs-")
+    if os.path.isdir(snapshot_dir):
+        shutil.copytree(snapshot_dir, serve_dir, dirs_exist_ok=True)
+    initial_snapshot = os.path.join(serve_dir, "0")
+    if not os.path.exists(initial_snapshot):
+        with open(initial_snapshot, "wb") as f:
+            f.write(empty_snapshot())
+
+    handler = functools.partial(http.server.SimpleHTTPRequestHandler, directory=serve_dir)
+    http.server.ThreadingHTTPServer(("", port), handler).serve_forever()
+
+
+if __name__ == "__main__":
+    main()
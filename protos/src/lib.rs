@@ -8,6 +8,39 @@ pub struct GetNodeIdResponse {
 	#[prost(string, tag = "1")]
 	pub node_id: ::prost::alloc::string::String,
 }
+/// Sign a message with the node's secret key.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignMessageRequest {
+	#[prost(bytes = "vec", tag = "1")]
+	pub message: ::prost::alloc::vec::Vec<u8>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignMessageResponse {
+	/// The zbase32-encoded signature, as produced by `lnd`'s `signmessage`.
+	#[prost(string, tag = "1")]
+	pub signature: ::prost::alloc::string::String,
+}
+/// Verify that a message was signed by the node with the given public key.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifySignatureRequest {
+	#[prost(bytes = "vec", tag = "1")]
+	pub message: ::prost::alloc::vec::Vec<u8>,
+	/// The zbase32-encoded signature.
+	#[prost(string, tag = "2")]
+	pub signature: ::prost::alloc::string::String,
+	/// The public key of the node that allegedly signed the message.
+	#[prost(string, tag = "3")]
+	pub public_key: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifySignatureResponse {
+	#[prost(bool, tag = "1")]
+	pub valid: bool,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct GetNodeStatusRequest {}
@@ -28,6 +61,13 @@ pub struct GetNodeStatusResponse {
 	pub latest_rgs_snapshot_timestamp: ::core::option::Option<u64>,
 	#[prost(uint64, optional, tag = "7")]
 	pub latest_node_announcement_broadcast_timestamp: ::core::option::Option<u64>,
+	/// The number of nodes in the network graph synced via P2P gossip or Rapid Gossip Sync. When
+	/// syncing via Rapid Gossip Sync, the graph was last updated at `latest_rgs_snapshot_timestamp`.
+	#[prost(uint64, tag = "8")]
+	pub network_graph_node_count: u64,
+	/// The number of channels in the network graph.
+	#[prost(uint64, tag = "9")]
+	pub network_graph_channel_count: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -53,14 +93,99 @@ pub struct OnchainReceiveResponse {
 pub struct OnchainSendRequest {
 	#[prost(string, tag = "1")]
 	pub address: ::prost::alloc::string::String,
+	/// The amount to send. If unset, all available funds are sent, minus fees and any on-chain funds
+	/// that need to be retained as an anchor channel reserve.
 	#[prost(uint64, optional, tag = "2")]
 	pub amount_sats: ::core::option::Option<u64>,
+	/// The fee rate to pay. If unset, the wallet's current estimate for on-chain payments is used.
+	#[prost(oneof = "onchain_send_request::FeeRate", tags = "3, 4")]
+	pub fee_rate: ::core::option::Option<onchain_send_request::FeeRate>,
+}
+/// Nested message and enum types in `OnchainSendRequest`.
+pub mod onchain_send_request {
+	/// The fee rate to pay. If unset, the wallet's current estimate for on-chain payments is used.
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum FeeRate {
+		/// An explicit fee rate, in sats per virtual byte.
+		#[prost(uint64, tag = "3")]
+		FeeRateSatPerVb(u64),
+		/// The number of blocks within which the transaction should confirm. The fee rate is taken
+		/// from the estimates returned by `GetFeeEstimatesRequest`, using the largest target not above
+		/// this one.
+		#[prost(uint32, tag = "4")]
+		ConfirmationTargetBlocks(u32),
+	}
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OnchainSendResponse {
 	#[prost(string, tag = "1")]
 	pub txid: ::prost::alloc::string::String,
+	/// Set instead of `txid` if the payment needs to be approved before it is sent, see
+	/// `ApprovePaymentRequest`.
+	#[prost(string, optional, tag = "4")]
+	pub approval_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Retrieve the fee rate estimates of the Esplora server the node is configured with.
+///
+/// These are the server's raw estimates, not the node's own: LDK Node doesn't expose the fee rates
+/// it uses. It derives them from the same server, but maps them onto its own confirmation targets
+/// and adjusts some of them.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetFeeEstimatesRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetFeeEstimatesResponse {
+	/// Ordered by confirmation target, fastest first.
+	#[prost(message, repeated, tag = "1")]
+	pub fee_rates: ::prost::alloc::vec::Vec<EsploraFeeRateEstimate>,
+	/// The last time the estimates were retrieved from the Esplora server, in seconds since the UNIX
+	/// epoch. Unset if they haven't been retrieved yet.
+	#[prost(uint64, optional, tag = "2")]
+	pub latest_update_timestamp: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EsploraFeeRateEstimate {
+	/// The number of blocks within which a transaction paying the fee rate is expected to confirm.
+	#[prost(uint32, tag = "1")]
+	pub target_blocks: u32,
+	/// The fee rate as reported by the Esplora server.
+	#[prost(double, tag = "2")]
+	pub sat_per_vb: f64,
+}
+/// List the transactions relevant to our on-chain wallet.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListOnchainTransactionsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListOnchainTransactionsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub transactions: ::prost::alloc::vec::Vec<OnchainTransaction>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OnchainTransaction {
+	#[prost(string, tag = "1")]
+	pub txid: ::prost::alloc::string::String,
+	/// The net effect of the transaction on our wallet balance. Negative for outgoing transactions.
+	#[prost(sint64, tag = "2")]
+	pub net_amount_sats: i64,
+	/// The fee paid by the transaction. Only known for transactions spending from our wallet.
+	#[prost(uint64, optional, tag = "3")]
+	pub fee_sats: ::core::option::Option<u64>,
+	/// The height of the block the transaction was confirmed in. Unset if unconfirmed.
+	#[prost(uint32, optional, tag = "4")]
+	pub confirmation_height: ::core::option::Option<u32>,
+	/// The timestamp of the block the transaction was confirmed in. Unset if unconfirmed.
+	#[prost(uint64, optional, tag = "5")]
+	pub confirmation_timestamp: ::core::option::Option<u64>,
+	/// The channel this transaction funded or swept funds from, if any.
+	#[prost(string, optional, tag = "6")]
+	pub channel_id: ::core::option::Option<::prost::alloc::string::String>,
 }
 /// Return a BOLT11 invoice for the given amount, if specified.
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -72,6 +197,9 @@ pub struct Bolt11ReceiveRequest {
 	pub expiry_secs: u32,
 	#[prost(uint64, optional, tag = "3")]
 	pub amount_msat: ::core::option::Option<u64>,
+	/// Metadata to attach to the payment for the invoice.
+	#[prost(message, optional, tag = "4")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -79,6 +207,159 @@ pub struct Bolt11ReceiveResponse {
 	#[prost(string, tag = "1")]
 	pub invoice: ::prost::alloc::string::String,
 }
+/// Return a BOLT11 invoice for the given payment hash. Payments to it are held until they are
+/// claimed via `Bolt11ClaimRequest` or failed via `Bolt11FailRequest`.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ReceiveForHashRequest {
+	#[prost(string, tag = "1")]
+	pub description: ::prost::alloc::string::String,
+	#[prost(uint32, tag = "2")]
+	pub expiry_secs: u32,
+	#[prost(uint64, optional, tag = "3")]
+	pub amount_msat: ::core::option::Option<u64>,
+	/// The hex-encoded payment hash.
+	#[prost(string, tag = "4")]
+	pub payment_hash: ::prost::alloc::string::String,
+	/// Metadata to attach to the payment for the invoice.
+	#[prost(message, optional, tag = "5")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ReceiveForHashResponse {
+	#[prost(string, tag = "1")]
+	pub invoice: ::prost::alloc::string::String,
+}
+/// List the inbound payments which are held until they are claimed or failed.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClaimablePaymentsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClaimablePaymentsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub payments: ::prost::alloc::vec::Vec<ClaimablePayment>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClaimablePayment {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+	#[prost(uint64, tag = "2")]
+	pub claimable_amount_msat: u64,
+	/// The block height by which the payment has to be claimed before it is failed back.
+	#[prost(uint32, optional, tag = "3")]
+	pub claim_deadline: ::core::option::Option<u32>,
+}
+/// Claim a held payment by revealing its preimage.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ClaimRequest {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+	/// The hex-encoded preimage.
+	#[prost(string, tag = "2")]
+	pub preimage: ::prost::alloc::string::String,
+	/// Defaults to the amount reported when the payment became claimable.
+	#[prost(uint64, optional, tag = "3")]
+	pub claimable_amount_msat: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ClaimResponse {}
+/// Fail a held payment back to the payer.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11FailRequest {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11FailResponse {}
+/// Decode a BOLT11 invoice.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11DecodeRequest {
+	#[prost(string, tag = "1")]
+	pub invoice: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11DecodeResponse {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+	#[prost(uint64, optional, tag = "2")]
+	pub amount_msat: ::core::option::Option<u64>,
+	#[prost(string, tag = "5")]
+	pub payee_node_id: ::prost::alloc::string::String,
+	/// The unix timestamp at which the invoice was created.
+	#[prost(uint64, tag = "6")]
+	pub timestamp: u64,
+	#[prost(uint64, tag = "7")]
+	pub expiry_secs: u64,
+	#[prost(bool, tag = "8")]
+	pub is_expired: bool,
+	#[prost(uint64, tag = "9")]
+	pub min_final_cltv_expiry_delta: u64,
+	#[prost(oneof = "bolt11_decode_response::Description", tags = "3, 4")]
+	pub description: ::core::option::Option<bolt11_decode_response::Description>,
+}
+/// Nested message and enum types in `Bolt11DecodeResponse`.
+pub mod bolt11_decode_response {
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum Description {
+		#[prost(string, tag = "3")]
+		DescriptionText(::prost::alloc::string::String),
+		/// The hex-encoded hash of the description, if only that was included in the invoice.
+		#[prost(string, tag = "4")]
+		DescriptionHash(::prost::alloc::string::String),
+	}
+}
+/// List the BOLT11 invoices issued via `Bolt11ReceiveRequest`.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListInvoicesRequest {
+	/// If set, only invoices in the given state are returned.
+	#[prost(enumeration = "InvoiceStatus", optional, tag = "1")]
+	pub status: ::core::option::Option<i32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListInvoicesResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub invoices: ::prost::alloc::vec::Vec<Invoice>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Invoice {
+	#[prost(string, tag = "1")]
+	pub invoice: ::prost::alloc::string::String,
+	#[prost(string, tag = "2")]
+	pub payment_hash: ::prost::alloc::string::String,
+	#[prost(uint64, optional, tag = "3")]
+	pub amount_msat: ::core::option::Option<u64>,
+	/// The unix timestamp at which the invoice was created.
+	#[prost(uint64, tag = "4")]
+	pub created_at: u64,
+	/// The unix timestamp at which the invoice expires.
+	#[prost(uint64, tag = "5")]
+	pub expires_at: u64,
+	#[prost(enumeration = "InvoiceStatus", tag = "6")]
+	pub status: i32,
+}
+/// Cancel an unpaid invoice we issued, failing back any HTLCs paying it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11CancelRequest {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11CancelResponse {}
 /// Send a payment for a BOLT11 invoice.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -87,12 +368,75 @@ pub struct Bolt11SendRequest {
 	pub invoice: ::prost::alloc::string::String,
 	#[prost(uint64, optional, tag = "2")]
 	pub amount_msat: ::core::option::Option<u64>,
+	/// Limits applied when routing the payment. If unset, the node's defaults apply.
+	#[prost(message, optional, tag = "3")]
+	pub sending_parameters: ::core::option::Option<SendingParameters>,
+	/// Metadata to attach to the payment.
+	#[prost(message, optional, tag = "4")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Bolt11SendResponse {
 	#[prost(message, optional, tag = "1")]
 	pub payment_id: ::core::option::Option<PaymentId>,
+	/// Set instead of `payment_id` if the payment needs to be approved before it is sent, see
+	/// `ApprovePaymentRequest`.
+	#[prost(string, optional, tag = "2")]
+	pub approval_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Routing limits for an outgoing payment. Unset fields fall back to the node's defaults.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SendingParameters {
+	/// The maximum total fees, in millisatoshis, that may be paid to route the payment.
+	#[prost(uint64, optional, tag = "1")]
+	pub max_total_routing_fee_msat: ::core::option::Option<u64>,
+	/// The maximum total CLTV expiry delta of the route.
+	#[prost(uint32, optional, tag = "2")]
+	pub max_total_cltv_expiry_delta: ::core::option::Option<u32>,
+	/// The maximum total fees, in parts per million of the payment amount, that may be paid to route
+	/// the payment. If `max_total_routing_fee_msat` is also set, the lower of the two limits applies.
+	#[prost(uint64, optional, tag = "3")]
+	pub max_total_routing_fee_ppm: ::core::option::Option<u64>,
+	/// The maximum number of paths the payment may be split across. Must not exceed 255.
+	#[prost(uint32, optional, tag = "4")]
+	pub max_path_count: ::core::option::Option<u32>,
+}
+/// Send probes along the route(s) we would use to pay a BOLT11 invoice, to learn about the
+/// liquidity available along them before paying it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ProbeRequest {
+	#[prost(string, tag = "1")]
+	pub invoice: ::prost::alloc::string::String,
+	/// Required for variable-amount invoices.
+	#[prost(uint64, optional, tag = "2")]
+	pub amount_msat: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Bolt11ProbeResponse {
+	/// Whether a route to the payee was found and the probes were sent. The probes themselves
+	/// complete asynchronously and update the node's view of the liquidity along the route.
+	#[prost(bool, tag = "1")]
+	pub route_found: bool,
+}
+/// Send probes along the route(s) we would use to pay the given node spontaneously.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpontaneousProbeRequest {
+	#[prost(string, tag = "1")]
+	pub node_id: ::prost::alloc::string::String,
+	#[prost(uint64, tag = "2")]
+	pub amount_msat: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpontaneousProbeResponse {
+	/// Whether a route to the node was found and the probes were sent.
+	#[prost(bool, tag = "1")]
+	pub route_found: bool,
 }
 /// Return a BOLT12 offer for the given amount, if specified.
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -109,7 +453,7 @@ pub struct Bolt12ReceiveResponse {
 	#[prost(string, tag = "1")]
 	pub offer: ::prost::alloc::string::String,
 }
-/// Send a payment for a BOLT11 invoice.
+/// Send a payment for a BOLT12 offer.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Bolt12SendRequest {
@@ -119,12 +463,97 @@ pub struct Bolt12SendRequest {
 	pub amount_msat: ::core::option::Option<u64>,
 	#[prost(string, optional, tag = "3")]
 	pub payer_note: ::core::option::Option<::prost::alloc::string::String>,
+	/// Metadata to attach to the payment.
+	#[prost(message, optional, tag = "4")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Bolt12SendResponse {
 	#[prost(message, optional, tag = "1")]
 	pub payment_id: ::core::option::Option<PaymentId>,
+	/// Set instead of `payment_id` if the payment needs to be approved before it is sent, see
+	/// `ApprovePaymentRequest`.
+	#[prost(string, optional, tag = "2")]
+	pub approval_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Send a spontaneous (keysend) payment to the given node.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpontaneousSendRequest {
+	#[prost(string, tag = "1")]
+	pub node_id: ::prost::alloc::string::String,
+	#[prost(uint64, tag = "2")]
+	pub amount_msat: u64,
+	/// Limits applied when routing the payment. If unset, the node's defaults apply.
+	#[prost(message, optional, tag = "3")]
+	pub sending_parameters: ::core::option::Option<SendingParameters>,
+	/// Metadata to attach to the payment.
+	#[prost(message, optional, tag = "4")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SpontaneousSendResponse {
+	#[prost(message, optional, tag = "1")]
+	pub payment_id: ::core::option::Option<PaymentId>,
+	/// Set instead of `payment_id` if the payment needs to be approved before it is sent, see
+	/// `ApprovePaymentRequest`.
+	#[prost(string, optional, tag = "2")]
+	pub approval_id: ::core::option::Option<::prost::alloc::string::String>,
+}
+/// Return a BIP21 URI combining a fresh on-chain address, a BOLT11 invoice and a BOLT12 offer for
+/// the given amount, so that the payer can pay using whichever method they support.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnifiedReceiveRequest {
+	#[prost(uint64, tag = "1")]
+	pub amount_sats: u64,
+	#[prost(string, tag = "2")]
+	pub description: ::prost::alloc::string::String,
+	/// The expiry of the BOLT11 invoice and BOLT12 offer.
+	#[prost(uint32, tag = "3")]
+	pub expiry_secs: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnifiedReceiveResponse {
+	#[prost(string, tag = "1")]
+	pub uri: ::prost::alloc::string::String,
+}
+/// Pay a BIP21 URI using the best method it offers, preferring a BOLT12 offer over a BOLT11
+/// invoice over an on-chain payment.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnifiedSendRequest {
+	#[prost(string, tag = "1")]
+	pub uri: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnifiedSendResponse {
+	/// Set instead of `payment` if the payment needs to be approved before it is sent, see
+	/// `ApprovePaymentRequest`.
+	#[prost(string, optional, tag = "4")]
+	pub approval_id: ::core::option::Option<::prost::alloc::string::String>,
+	#[prost(oneof = "unified_send_response::Payment", tags = "1, 2, 3")]
+	pub payment: ::core::option::Option<unified_send_response::Payment>,
+}
+/// Nested message and enum types in `UnifiedSendResponse`.
+pub mod unified_send_response {
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum Payment {
+		/// Set if the URI was paid on-chain.
+		#[prost(string, tag = "1")]
+		Txid(::prost::alloc::string::String),
+		/// Set if the URI was paid via its BOLT11 invoice.
+		#[prost(message, tag = "2")]
+		Bolt11PaymentId(super::PaymentId),
+		/// Set if the URI was paid via its BOLT12 offer.
+		#[prost(message, tag = "3")]
+		Bolt12PaymentId(super::PaymentId),
+	}
 }
 /// An identifier for making a payment.
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -142,6 +571,199 @@ pub struct ListChannelsResponse {
 	#[prost(message, repeated, tag = "1")]
 	pub channels: ::prost::alloc::vec::Vec<Channel>,
 }
+/// List channels that have been closed, most recently closed first.
+///
+/// Only channels closed since the server started recording channel history are returned.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClosedChannelsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListClosedChannelsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub channels: ::prost::alloc::vec::Vec<ClosedChannel>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClosedChannel {
+	#[prost(string, tag = "1")]
+	pub channel_id: ::prost::alloc::string::String,
+	#[prost(string, tag = "2")]
+	pub user_channel_id: ::prost::alloc::string::String,
+	#[prost(string, optional, tag = "3")]
+	pub counterparty_node_id: ::core::option::Option<::prost::alloc::string::String>,
+	/// Unset, as are the fields up to `ready_at`, if the channel was opened before the server
+	/// started recording channel history.
+	#[prost(message, optional, tag = "4")]
+	pub funding_txo: ::core::option::Option<Outpoint>,
+	#[prost(uint64, optional, tag = "5")]
+	pub channel_value_sats: ::core::option::Option<u64>,
+	#[prost(bool, optional, tag = "6")]
+	pub is_outbound: ::core::option::Option<bool>,
+	/// The unix timestamp at which the funding transaction was broadcast.
+	#[prost(uint64, optional, tag = "7")]
+	pub pending_at: ::core::option::Option<u64>,
+	/// The unix timestamp at which the channel became usable.
+	#[prost(uint64, optional, tag = "8")]
+	pub ready_at: ::core::option::Option<u64>,
+	/// The unix timestamp at which the channel closed.
+	#[prost(uint64, tag = "9")]
+	pub closed_at: u64,
+	/// A description of why the channel was closed, if known.
+	#[prost(string, optional, tag = "10")]
+	pub closure_reason: ::core::option::Option<::prost::alloc::string::String>,
+	/// The transaction spending the funding output. It is looked up in the background after the
+	/// channel closed, so it may be unset for a while.
+	#[prost(string, optional, tag = "11")]
+	pub closing_txid: ::core::option::Option<::prost::alloc::string::String>,
+	/// Our balance claimable on-chain when the channel closed, excluding HTLCs.
+	#[prost(uint64, tag = "12")]
+	pub final_balance_sats: u64,
+	/// The HTLCs we may still have been able to claim on-chain when the channel closed.
+	#[prost(uint64, tag = "13")]
+	pub final_pending_htlc_balance_sats: u64,
+}
+/// List outgoing payments parked by the spending policy until they are approved or rejected.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPendingApprovalsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListPendingApprovalsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub payments: ::prost::alloc::vec::Vec<PendingApproval>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PendingApproval {
+	#[prost(string, tag = "1")]
+	pub id: ::prost::alloc::string::String,
+	/// One of `bolt11`, `bolt12`, `spontaneous` or `onchain`.
+	#[prost(string, tag = "2")]
+	pub kind: ::prost::alloc::string::String,
+	#[prost(uint64, tag = "3")]
+	pub amount_msat: u64,
+	/// The recipient's node id, or the address for on-chain payments.
+	#[prost(string, tag = "4")]
+	pub destination: ::prost::alloc::string::String,
+	/// The unix timestamp at which the payment was parked.
+	#[prost(uint64, tag = "5")]
+	pub created_at: u64,
+}
+/// Send a parked payment. The payment is still subject to the spending limits.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApprovePaymentRequest {
+	#[prost(string, tag = "1")]
+	pub id: ::prost::alloc::string::String,
+	/// Must match the `approval_token` of the server's spending policy.
+	#[prost(string, tag = "2")]
+	pub approval_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ApprovePaymentResponse {
+	#[prost(oneof = "approve_payment_response::Payment", tags = "1, 2, 3, 4")]
+	pub payment: ::core::option::Option<approve_payment_response::Payment>,
+}
+/// Nested message and enum types in `ApprovePaymentResponse`.
+pub mod approve_payment_response {
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum Payment {
+		#[prost(message, tag = "1")]
+		Bolt11(super::Bolt11SendResponse),
+		#[prost(message, tag = "2")]
+		Bolt12(super::Bolt12SendResponse),
+		#[prost(message, tag = "3")]
+		Spontaneous(super::SpontaneousSendResponse),
+		#[prost(message, tag = "4")]
+		Onchain(super::OnchainSendResponse),
+	}
+}
+/// Drop a parked payment without sending it.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectPaymentRequest {
+	#[prost(string, tag = "1")]
+	pub id: ::prost::alloc::string::String,
+	/// Must match the `approval_token` of the server's spending policy.
+	#[prost(string, tag = "2")]
+	pub approval_token: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RejectPaymentResponse {}
+/// List entries of the audit log of state-changing calls, oldest first.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditLogRequest {
+	/// The number of entries to skip.
+	#[prost(uint32, tag = "1")]
+	pub offset: u32,
+	/// The maximum number of entries to return. Defaults to 100.
+	#[prost(uint32, optional, tag = "2")]
+	pub limit: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditLogResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub entries: ::prost::alloc::vec::Vec<AuditLogEntry>,
+	/// The total number of entries in the log.
+	#[prost(uint64, tag = "2")]
+	pub total_count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AuditLogEntry {
+	#[prost(uint64, tag = "1")]
+	pub sequence: u64,
+	/// The unix timestamp at which the call was handled.
+	#[prost(uint64, tag = "2")]
+	pub timestamp: u64,
+	/// The address the call came from.
+	#[prost(string, tag = "3")]
+	pub identity: ::prost::alloc::string::String,
+	#[prost(string, tag = "4")]
+	pub path: ::prost::alloc::string::String,
+	/// The request with sensitive fields redacted, or empty if the entry records an outcome.
+	#[prost(string, tag = "5")]
+	pub request: ::prost::alloc::string::String,
+	/// Set if the call failed.
+	#[prost(string, optional, tag = "6")]
+	pub error: ::core::option::Option<::prost::alloc::string::String>,
+	/// The hash of the previous entry, or all zeros for the first entry.
+	#[prost(string, tag = "7")]
+	pub prev_hash: ::prost::alloc::string::String,
+	/// The hex-encoded SHA-256 hash of the entry, committing to all of its other fields.
+	#[prost(string, tag = "8")]
+	pub hash: ::prost::alloc::string::String,
+	/// Set if the entry records the outcome of a call, to the sequence number of the entry recording
+	/// the call itself. Every call is recorded before it is handled, and once more with its outcome.
+	#[prost(uint64, optional, tag = "9")]
+	pub outcome_of: ::core::option::Option<u64>,
+}
+/// Check that no entry of the audit log was modified or removed, by recomputing its chain of
+/// hashes.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyAuditLogRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerifyAuditLogResponse {
+	#[prost(bool, tag = "1")]
+	pub valid: bool,
+	#[prost(uint64, tag = "2")]
+	pub entry_count: u64,
+	/// The hash of the latest entry. Recording it elsewhere allows detecting the removal of entries
+	/// at the end of the log.
+	#[prost(string, tag = "3")]
+	pub head_hash: ::prost::alloc::string::String,
+	/// The sequence number of the first entry whose hash or link to its predecessor doesn't match.
+	#[prost(uint64, optional, tag = "4")]
+	pub first_invalid_sequence: ::core::option::Option<u64>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenChannelRequest {
@@ -155,35 +777,177 @@ pub struct OpenChannelRequest {
 	pub push_to_counterparty_msat: ::core::option::Option<u64>,
 	#[prost(bool, tag = "5")]
 	pub announce_channel: bool,
+	/// The config to open the channel with. If unset, or for any fields left unset, the node's
+	/// defaults are used.
+	#[prost(message, optional, tag = "6")]
+	pub channel_config: ::core::option::Option<ChannelConfig>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenChannelResponse {
-	#[prost(bytes = "vec", tag = "1")]
-	pub user_channel_id: ::prost::alloc::vec::Vec<u8>,
+	/// The local channel ID, formatted as a decimal `u128`. Unlike `channel_id`, it never changes, so
+	/// use it to refer to the channel in subsequent requests.
+	#[prost(string, tag = "4")]
+	pub user_channel_id: ::prost::alloc::string::String,
+	/// The channel ID at the time of the response. Until the funding transaction has been
+	/// negotiated this is the temporary channel ID, which will change once the channel is pending.
+	#[prost(string, optional, tag = "2")]
+	pub channel_id: ::core::option::Option<::prost::alloc::string::String>,
+	/// The channel's funding transaction output, if already known.
+	#[prost(message, optional, tag = "3")]
+	pub funding_txo: ::core::option::Option<Outpoint>,
+}
+/// Identifies a single channel by any of its identifiers.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelIdentifier {
+	#[prost(oneof = "channel_identifier::Id", tags = "1, 2, 3")]
+	pub id: ::core::option::Option<channel_identifier::Id>,
+}
+/// Nested message and enum types in `ChannelIdentifier`.
+pub mod channel_identifier {
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum Id {
+		/// The local channel ID, formatted as a decimal `u128` as returned by `OpenChannelResponse` and
+		/// `Channel`.
+		#[prost(string, tag = "1")]
+		UserChannelId(::prost::alloc::string::String),
+		/// The channel ID, as returned by `Channel`.
+		#[prost(string, tag = "2")]
+		ChannelId(::prost::alloc::string::String),
+		/// The channel's funding transaction output.
+		#[prost(message, tag = "3")]
+		FundingTxo(super::Outpoint),
+	}
 }
+/// Cooperatively close one channel or all channels with a given counterparty.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct CloseChannelRequest {
-	#[prost(bytes = "vec", tag = "1")]
-	pub user_channel_id: ::prost::alloc::vec::Vec<u8>,
-	#[prost(string, tag = "2")]
-	pub counterparty_node_id: ::prost::alloc::string::String,
+	/// The channel to close. Ignored if `all_channels` is set.
+	#[prost(message, optional, tag = "4")]
+	pub channel: ::core::option::Option<ChannelIdentifier>,
+	/// The node ID of the channel's counterparty. If set, the identified channel must be with this
+	/// counterparty. Required if `all_channels` is set.
+	#[prost(string, optional, tag = "2")]
+	pub counterparty_node_id: ::core::option::Option<::prost::alloc::string::String>,
+	/// If set, all channels with `counterparty_node_id` are closed.
+	#[prost(bool, tag = "5")]
+	pub all_channels: bool,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct CloseChannelResponse {}
+pub struct CloseChannelResponse {
+	/// The local channel IDs of all channels we initiated closing.
+	#[prost(string, repeated, tag = "1")]
+	pub user_channel_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+	/// The channels we failed to close. The request only fails as a whole if no channel could be
+	/// closed.
+	#[prost(message, repeated, tag = "2")]
+	pub failures: ::prost::alloc::vec::Vec<ChannelCloseFailure>,
+}
+/// Force-close one channel or all channels with a given counterparty.
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ForceCloseChannelRequest {
-	#[prost(bytes = "vec", tag = "1")]
-	pub user_channel_id: ::prost::alloc::vec::Vec<u8>,
+	/// The channel to force-close. Ignored if `all_channels` is set.
+	#[prost(message, optional, tag = "4")]
+	pub channel: ::core::option::Option<ChannelIdentifier>,
+	/// The node ID of the channel's counterparty. If set, the identified channel must be with this
+	/// counterparty. Required if `all_channels` is set.
+	#[prost(string, optional, tag = "2")]
+	pub counterparty_node_id: ::core::option::Option<::prost::alloc::string::String>,
+	/// If set, all channels with `counterparty_node_id` are force-closed.
+	#[prost(bool, tag = "5")]
+	pub all_channels: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForceCloseChannelResponse {
+	/// The local channel IDs of all channels we force-closed.
+	#[prost(string, repeated, tag = "1")]
+	pub user_channel_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+	/// The channels we failed to force-close. The request only fails as a whole if no channel could
+	/// be force-closed.
+	#[prost(message, repeated, tag = "2")]
+	pub failures: ::prost::alloc::vec::Vec<ChannelCloseFailure>,
+}
+/// A channel that could not be closed as part of a close or force-close request.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelCloseFailure {
+	/// The local channel ID, formatted as a decimal `u128`.
+	#[prost(string, tag = "1")]
+	pub user_channel_id: ::prost::alloc::string::String,
+	/// Why the channel could not be closed.
+	#[prost(string, tag = "2")]
+	pub error: ::prost::alloc::string::String,
+}
+/// Update the config of an existing channel.
+///
+/// Any fields left unset in the given config retain their current value.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateChannelConfigRequest {
+	/// The local channel ID, formatted as a decimal `u128` as returned by `OpenChannelResponse` and
+	/// `Channel`.
+	#[prost(string, tag = "4")]
+	pub user_channel_id: ::prost::alloc::string::String,
 	#[prost(string, tag = "2")]
 	pub counterparty_node_id: ::prost::alloc::string::String,
+	#[prost(message, optional, tag = "3")]
+	pub channel_config: ::core::option::Option<ChannelConfig>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct ForceCloseChannelResponse {}
+pub struct UpdateChannelConfigResponse {}
+/// Options which apply on a per-channel basis and may change at runtime or based on negotiation
+/// with our counterparty.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ChannelConfig {
+	/// Amount (in millionths of a satoshi) charged per satoshi for payments forwarded outbound
+	/// over the channel.
+	#[prost(uint32, optional, tag = "1")]
+	pub forwarding_fee_proportional_millionths: ::core::option::Option<u32>,
+	/// Amount (in milli-satoshi) charged for payments forwarded outbound over the channel, in
+	/// excess of `forwarding_fee_proportional_millionths`.
+	#[prost(uint32, optional, tag = "2")]
+	pub forwarding_fee_base_msat: ::core::option::Option<u32>,
+	/// The difference in the CLTV value between incoming HTLCs and an outbound HTLC forwarded over
+	/// the channel. Must fit in 16 bits.
+	#[prost(uint32, optional, tag = "3")]
+	pub cltv_expiry_delta: ::core::option::Option<u32>,
+	/// The additional fee we're willing to pay to avoid waiting for the counterparty's
+	/// `to_self_delay` to reclaim funds, i.e., the amount in excess of the normal closing fee
+	/// estimate we'll accept for a cooperative close.
+	#[prost(uint64, optional, tag = "6")]
+	pub force_close_avoidance_max_fee_satoshis: ::core::option::Option<u64>,
+	/// If set, allows this channel's counterparty to skim an additional fee off this node's inbound
+	/// HTLCs. Useful for liquidity providers to offload on-chain channel costs to end users.
+	#[prost(bool, optional, tag = "7")]
+	pub accept_underpaying_htlcs: ::core::option::Option<bool>,
+	/// The limit on our exposure to dust HTLCs, i.e., HTLCs whose value would be burned to fees
+	/// rather than claimed on-chain if the channel is force-closed.
+	#[prost(oneof = "channel_config::MaxDustHtlcExposure", tags = "4, 5")]
+	pub max_dust_htlc_exposure: ::core::option::Option<channel_config::MaxDustHtlcExposure>,
+}
+/// Nested message and enum types in `ChannelConfig`.
+pub mod channel_config {
+	/// The limit on our exposure to dust HTLCs, i.e., HTLCs whose value would be burned to fees
+	/// rather than claimed on-chain if the channel is force-closed.
+	#[allow(clippy::derive_partial_eq_without_eq)]
+	#[derive(Clone, PartialEq, ::prost::Oneof)]
+	pub enum MaxDustHtlcExposure {
+		/// Sets a fixed limit on the total dust exposure in millisatoshis.
+		#[prost(uint64, tag = "4")]
+		FixedLimitMsat(u64),
+		/// Sets a limit which is a multiple of the current feerate in sat/KW.
+		#[prost(uint64, tag = "5")]
+		FeeRateMultiplier(u64),
+	}
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Channel {
@@ -293,6 +1057,11 @@ pub struct Channel {
 	/// excess of \[`forwarding_fee_proportional_millionths`\].
 	#[prost(uint32, tag = "21")]
 	pub forwarding_fee_base_msat: u32,
+	/// The local channel ID, formatted as a decimal `u128`. This is assigned by us when the channel
+	/// is created and, unlike `channel_id`, never changes. It is used to identify the channel in
+	/// close, force-close and config update requests.
+	#[prost(string, tag = "22")]
+	pub user_channel_id: ::prost::alloc::string::String,
 }
 /// Represents to transaction output.
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -514,6 +1283,27 @@ pub struct PaymentDetails {
 	pub status: i32,
 	#[prost(uint64, tag = "6")]
 	pub latest_update_timestamp: u64,
+	/// The routing fees paid for a successful outbound payment.
+	#[prost(uint64, optional, tag = "7")]
+	pub fee_paid_msat: ::core::option::Option<u64>,
+	/// Why a failed outbound payment failed, if known.
+	#[prost(enumeration = "PaymentFailureReason", optional, tag = "8")]
+	pub failure_reason: ::core::option::Option<i32>,
+	/// The metadata attached to the payment when it was created.
+	#[prost(message, optional, tag = "9")]
+	pub metadata: ::core::option::Option<PaymentMetadata>,
+}
+/// Caller-defined metadata attached to a payment, stored by the server.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PaymentMetadata {
+	#[prost(string, repeated, tag = "1")]
+	pub labels: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+	#[prost(string, optional, tag = "2")]
+	pub memo: ::core::option::Option<::prost::alloc::string::String>,
+	/// An identifier of the payment in an external system, such as an order id.
+	#[prost(string, optional, tag = "3")]
+	pub external_id: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -605,7 +1395,14 @@ pub struct LspFeeLimits {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
-pub struct PaymentsHistoryRequest {}
+pub struct PaymentsHistoryRequest {
+	/// If set, only payments carrying the given label are returned.
+	#[prost(string, optional, tag = "1")]
+	pub label: ::core::option::Option<::prost::alloc::string::String>,
+	/// If set, only payments with the given external id are returned.
+	#[prost(string, optional, tag = "2")]
+	pub external_id: ::core::option::Option<::prost::alloc::string::String>,
+}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct PaymentsHistoryResponse {
@@ -624,6 +1421,339 @@ pub struct GetPaymentDetailsResponse {
 	#[prost(message, optional, tag = "1")]
 	pub payment: ::core::option::Option<PaymentDetails>,
 }
+/// Look up the payments with the given hex-encoded payment hash.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPaymentsByHashRequest {
+	#[prost(string, tag = "1")]
+	pub payment_hash: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetPaymentsByHashResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub payments: ::prost::alloc::vec::Vec<PaymentDetails>,
+}
+/// List the payments we forwarded, newest first, along with the fee income they earned.
+///
+/// Fees are attributed to the outbound channel and peer of a forward, as they are charged according
+/// to the outbound channel's forwarding policy.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListForwardsRequest {
+	/// If set, only forwards settled at or after this unix timestamp are included.
+	#[prost(uint64, optional, tag = "1")]
+	pub start_timestamp: ::core::option::Option<u64>,
+	/// If set, only forwards settled before this unix timestamp are included.
+	#[prost(uint64, optional, tag = "2")]
+	pub end_timestamp: ::core::option::Option<u64>,
+	/// The number of entries to skip, for pagination.
+	#[prost(uint32, tag = "3")]
+	pub offset: u32,
+	/// The maximum number of entries to return. Defaults to 100.
+	#[prost(uint32, optional, tag = "4")]
+	pub limit: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListForwardsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub forwards: ::prost::alloc::vec::Vec<Forward>,
+	#[prost(uint64, tag = "2")]
+	pub total_count: u64,
+	#[prost(uint64, tag = "3")]
+	pub total_fee_earned_msat: u64,
+	#[prost(uint64, tag = "4")]
+	pub total_amount_forwarded_msat: u64,
+	/// Ordered by fee earned, highest first.
+	#[prost(message, repeated, tag = "5")]
+	pub income_by_channel: ::prost::alloc::vec::Vec<ForwardingIncome>,
+	/// Ordered by fee earned, highest first. Forwards whose outbound peer is unknown are omitted.
+	#[prost(message, repeated, tag = "6")]
+	pub income_by_peer: ::prost::alloc::vec::Vec<ForwardingIncome>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Forward {
+	/// The index of the forward, in the order forwards were settled.
+	#[prost(string, tag = "1")]
+	pub id: ::prost::alloc::string::String,
+	#[prost(string, tag = "2")]
+	pub prev_channel_id: ::prost::alloc::string::String,
+	#[prost(string, tag = "3")]
+	pub next_channel_id: ::prost::alloc::string::String,
+	/// The node id of the peer we received the payment from. Unset for HTLCs received before the
+	/// node ran LDK Node v0.5.
+	#[prost(string, optional, tag = "4")]
+	pub prev_counterparty_node_id: ::core::option::Option<::prost::alloc::string::String>,
+	/// The node id of the peer we forwarded the payment to. Unset for HTLCs received before the
+	/// node ran LDK Node v0.5.
+	#[prost(string, optional, tag = "5")]
+	pub next_counterparty_node_id: ::core::option::Option<::prost::alloc::string::String>,
+	#[prost(uint64, optional, tag = "6")]
+	pub total_fee_earned_msat: ::core::option::Option<u64>,
+	#[prost(uint64, optional, tag = "7")]
+	pub outbound_amount_forwarded_msat: ::core::option::Option<u64>,
+	/// The unix timestamp at which the forward settled.
+	#[prost(uint64, tag = "8")]
+	pub timestamp: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ForwardingIncome {
+	/// The channel id or node id the income is attributed to.
+	#[prost(string, tag = "1")]
+	pub id: ::prost::alloc::string::String,
+	#[prost(uint64, tag = "2")]
+	pub forward_count: u64,
+	#[prost(uint64, tag = "3")]
+	pub fee_earned_msat: u64,
+	#[prost(uint64, tag = "4")]
+	pub amount_forwarded_msat: u64,
+}
+/// Export settled activity for accounting: succeeded payments, forwarding income and confirmed
+/// on-chain transactions, including the costs of opening and closing channels. Records are ordered
+/// by timestamp.
+///
+/// Both formats contain the same fields, in the following order:
+/// - timestamp: the unix timestamp at which the entry settled.
+/// - category: one of payment_received, payment_sent, forward, onchain_receive, onchain_send,
+///   channel_open or channel_close.
+/// - id: the hex-encoded payment id, the txid or the index of the forward.
+/// - amount_msat: the effect on our balance excluding fees. Negative for outgoing funds.
+/// - fee_paid_msat: the routing or on-chain fee we paid.
+/// - fee_earned_msat: the fee we earned for a forward.
+/// - channel_id: the channel opened or closed, or the channel a payment was forwarded over.
+/// - external_id, memo: the metadata attached to a payment.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportRequest {
+	/// If set, only entries settled at or after this unix timestamp are exported.
+	#[prost(uint64, optional, tag = "1")]
+	pub start_timestamp: ::core::option::Option<u64>,
+	/// If set, only entries settled before this unix timestamp are exported.
+	#[prost(uint64, optional, tag = "2")]
+	pub end_timestamp: ::core::option::Option<u64>,
+	#[prost(enumeration = "ExportFormat", tag = "3")]
+	pub format: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportResponse {
+	#[prost(string, tag = "1")]
+	pub data: ::prost::alloc::string::String,
+}
+/// List the short channel ids known to the node's network graph, ordered by short channel id.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGraphChannelsRequest {
+	/// The number of entries to skip, for pagination.
+	#[prost(uint32, tag = "1")]
+	pub offset: u32,
+	/// The maximum number of entries to return. Defaults to 100.
+	#[prost(uint32, optional, tag = "2")]
+	pub limit: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGraphChannelsResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub channels: ::prost::alloc::vec::Vec<GraphChannel>,
+	/// The total number of channels in the network graph.
+	#[prost(uint64, tag = "2")]
+	pub total_count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGraphChannelRequest {
+	#[prost(uint64, tag = "1")]
+	pub short_channel_id: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGraphChannelResponse {
+	/// Unset if the channel is not known to the network graph.
+	#[prost(message, optional, tag = "1")]
+	pub channel: ::core::option::Option<GraphChannel>,
+}
+/// List the nodes known to the node's network graph, ordered by node id.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGraphNodesRequest {
+	/// The number of entries to skip, for pagination.
+	#[prost(uint32, tag = "1")]
+	pub offset: u32,
+	/// The maximum number of entries to return. Defaults to 100.
+	#[prost(uint32, optional, tag = "2")]
+	pub limit: ::core::option::Option<u32>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListGraphNodesResponse {
+	#[prost(message, repeated, tag = "1")]
+	pub nodes: ::prost::alloc::vec::Vec<GraphNode>,
+	/// The total number of nodes in the network graph.
+	#[prost(uint64, tag = "2")]
+	pub total_count: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGraphNodeRequest {
+	#[prost(string, tag = "1")]
+	pub node_id: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetGraphNodeResponse {
+	/// Unset if the node is not known to the network graph.
+	#[prost(message, optional, tag = "1")]
+	pub node: ::core::option::Option<GraphNode>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphChannel {
+	#[prost(uint64, tag = "1")]
+	pub short_channel_id: u64,
+	#[prost(string, tag = "2")]
+	pub node_one: ::prost::alloc::string::String,
+	/// The direction from `node_one` to `node_two`, unset if no update has been seen yet.
+	#[prost(message, optional, tag = "3")]
+	pub one_to_two: ::core::option::Option<GraphChannelUpdate>,
+	#[prost(string, tag = "4")]
+	pub node_two: ::prost::alloc::string::String,
+	/// The direction from `node_two` to `node_one`, unset if no update has been seen yet.
+	#[prost(message, optional, tag = "5")]
+	pub two_to_one: ::core::option::Option<GraphChannelUpdate>,
+	/// Unset if the channel's funding output hasn't been looked up.
+	#[prost(uint64, optional, tag = "6")]
+	pub capacity_sats: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphChannelUpdate {
+	/// The unix timestamp of the latest channel update.
+	#[prost(uint32, tag = "1")]
+	pub last_update: u32,
+	#[prost(bool, tag = "2")]
+	pub enabled: bool,
+	#[prost(uint32, tag = "3")]
+	pub cltv_expiry_delta: u32,
+	#[prost(uint64, tag = "4")]
+	pub htlc_minimum_msat: u64,
+	#[prost(uint64, tag = "5")]
+	pub htlc_maximum_msat: u64,
+	#[prost(uint32, tag = "6")]
+	pub fee_base_msat: u32,
+	#[prost(uint32, tag = "7")]
+	pub fee_proportional_millionths: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphNode {
+	#[prost(string, tag = "1")]
+	pub node_id: ::prost::alloc::string::String,
+	#[prost(uint64, repeated, tag = "2")]
+	pub short_channel_ids: ::prost::alloc::vec::Vec<u64>,
+	/// Unset if no node announcement has been seen yet.
+	#[prost(message, optional, tag = "3")]
+	pub announcement: ::core::option::Option<GraphNodeAnnouncement>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GraphNodeAnnouncement {
+	/// The unix timestamp of the latest node announcement.
+	#[prost(uint32, tag = "1")]
+	pub last_update: u32,
+	#[prost(string, tag = "2")]
+	pub alias: ::prost::alloc::string::String,
+	/// The hex-encoded color of the node.
+	#[prost(string, tag = "3")]
+	pub rgb: ::prost::alloc::string::String,
+	#[prost(string, repeated, tag = "4")]
+	pub addresses: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum InvoiceStatus {
+	Open = 0,
+	Paid = 1,
+	Expired = 2,
+	Cancelled = 3,
+}
+impl InvoiceStatus {
+	/// String value of the enum field names used in the ProtoBuf definition.
+	///
+	/// The values are not transformed in any way and thus are considered stable
+	/// (if the ProtoBuf definition does not change) and safe for programmatic use.
+	pub fn as_str_name(&self) -> &'static str {
+		match self {
+			InvoiceStatus::Open => "OPEN",
+			InvoiceStatus::Paid => "PAID",
+			InvoiceStatus::Expired => "EXPIRED",
+			InvoiceStatus::Cancelled => "CANCELLED",
+		}
+	}
+	/// Creates an enum from field names used in the ProtoBuf definition.
+	pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+		match value {
+			"OPEN" => Some(Self::Open),
+			"PAID" => Some(Self::Paid),
+			"EXPIRED" => Some(Self::Expired),
+			"CANCELLED" => Some(Self::Cancelled),
+			_ => None,
+		}
+	}
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum PaymentFailureReason {
+	RecipientRejected = 0,
+	UserAbandoned = 1,
+	RetriesExhausted = 2,
+	PaymentExpired = 3,
+	RouteNotFound = 4,
+	UnexpectedError = 5,
+	UnknownRequiredFeatures = 6,
+	InvoiceRequestExpired = 7,
+	InvoiceRequestRejected = 8,
+	BlindedPathCreationFailed = 9,
+}
+impl PaymentFailureReason {
+	/// String value of the enum field names used in the ProtoBuf definition.
+	///
+	/// The values are not transformed in any way and thus are considered stable
+	/// (if the ProtoBuf definition does not change) and safe for programmatic use.
+	pub fn as_str_name(&self) -> &'static str {
+		match self {
+			PaymentFailureReason::RecipientRejected => "RECIPIENT_REJECTED",
+			PaymentFailureReason::UserAbandoned => "USER_ABANDONED",
+			PaymentFailureReason::RetriesExhausted => "RETRIES_EXHAUSTED",
+			PaymentFailureReason::PaymentExpired => "PAYMENT_EXPIRED",
+			PaymentFailureReason::RouteNotFound => "ROUTE_NOT_FOUND",
+			PaymentFailureReason::UnexpectedError => "UNEXPECTED_ERROR",
+			PaymentFailureReason::UnknownRequiredFeatures => "UNKNOWN_REQUIRED_FEATURES",
+			PaymentFailureReason::InvoiceRequestExpired => "INVOICE_REQUEST_EXPIRED",
+			PaymentFailureReason::InvoiceRequestRejected => "INVOICE_REQUEST_REJECTED",
+			PaymentFailureReason::BlindedPathCreationFailed => "BLINDED_PATH_CREATION_FAILED",
+		}
+	}
+	/// Creates an enum from field names used in the ProtoBuf definition.
+	pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+		match value {
+			"RECIPIENT_REJECTED" => Some(Self::RecipientRejected),
+			"USER_ABANDONED" => Some(Self::UserAbandoned),
+			"RETRIES_EXHAUSTED" => Some(Self::RetriesExhausted),
+			"PAYMENT_EXPIRED" => Some(Self::PaymentExpired),
+			"ROUTE_NOT_FOUND" => Some(Self::RouteNotFound),
+			"UNEXPECTED_ERROR" => Some(Self::UnexpectedError),
+			"UNKNOWN_REQUIRED_FEATURES" => Some(Self::UnknownRequiredFeatures),
+			"INVOICE_REQUEST_EXPIRED" => Some(Self::InvoiceRequestExpired),
+			"INVOICE_REQUEST_REJECTED" => Some(Self::InvoiceRequestRejected),
+			"BLINDED_PATH_CREATION_FAILED" => Some(Self::BlindedPathCreationFailed),
+			_ => None,
+		}
+	}
+}
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
 pub enum PaymentDirection {
@@ -679,3 +1809,31 @@ impl PaymentStatus {
 		}
 	}
 }
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ExportFormat {
+	/// Comma-separated values with a header row.
+	Csv = 0,
+	/// One JSON object per line.
+	JsonLines = 1,
+}
+impl ExportFormat {
+	/// String value of the enum field names used in the ProtoBuf definition.
+	///
+	/// The values are not transformed in any way and thus are considered stable
+	/// (if the ProtoBuf definition does not change) and safe for programmatic use.
+	pub fn as_str_name(&self) -> &'static str {
+		match self {
+			ExportFormat::Csv => "CSV",
+			ExportFormat::JsonLines => "JSON_LINES",
+		}
+	}
+	/// Creates an enum from field names used in the ProtoBuf definition.
+	pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+		match value {
+			"CSV" => Some(Self::Csv),
+			"JSON_LINES" => Some(Self::JsonLines),
+			_ => None,
+		}
+	}
+}
@@ -5,16 +5,62 @@ set +eux pipefail
 pushd cli
 cargo run -- --base-url localhost:3000 node-id
 cargo run -- --base-url localhost:3000 node-status
+# The docker compose setup serves an empty regtest Rapid Gossip Sync snapshot (see
+# contrib/rgs/serve-snapshots.py), so node-status reports latest_rgs_snapshot_timestamp once it has
+# been applied. For non-zero network graph node and channel counts, put a populated snapshot in
+# ./rgs-snapshots, or point `rgs_server_url` at a server for the node's network (e.g.
+# https://rgs.mutinynet.com/snapshot/ with mutinynet-config.json), and run node-status again.
+cargo run -- --base-url localhost:3000 sign-message "message"
+cargo run -- --base-url localhost:3000 verify-signature "message" signature 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190
 cargo run -- --base-url localhost:3000 new-address
 cargo run -- --base-url localhost:3000 send-onchain addy 1000
+cargo run -- --base-url localhost:3000 send-onchain addy 1000 --fee-rate-sat-per-vb 2
+cargo run -- --base-url localhost:3000 onchain-transactions
 cargo run -- --base-url localhost:3000 bolt11-receive "description" 1000 1000
+cargo run -- --base-url localhost:3000 bolt11-receive "description" 1000 1000 --label shop --label web --memo "memo" --external-id order-1
+cargo run -- --base-url localhost:3000 bolt11-receive-for-hash "description" 1000 0000000000000000000000000000000000000000000000000000000000000000 1000
+cargo run -- --base-url localhost:3000 claimable-payments
+cargo run -- --base-url localhost:3000 bolt11-claim 0000000000000000000000000000000000000000000000000000000000000000 0000000000000000000000000000000000000000000000000000000000000000
+cargo run -- --base-url localhost:3000 bolt11-fail 0000000000000000000000000000000000000000000000000000000000000000
+cargo run -- --base-url localhost:3000 bolt11-decode invoice
+cargo run -- --base-url localhost:3000 list-invoices
+cargo run -- --base-url localhost:3000 list-invoices --status open
+cargo run -- --base-url localhost:3000 bolt11-cancel 0000000000000000000000000000000000000000000000000000000000000000
 cargo run -- --base-url localhost:3000 bolt11-send invoice 1000
+cargo run -- --base-url localhost:3000 bolt11-send invoice 1000 --max-total-routing-fee-msat 100 --max-total-cltv-expiry-delta 1008
+cargo run -- --base-url localhost:3000 bolt11-send invoice 1000 --max-total-routing-fee-ppm 5000 --max-path-count 3
+cargo run -- --base-url localhost:3000 bolt11-send invoice 1000 --label supplier --external-id order-2
+cargo run -- --base-url localhost:3000 bolt12-send offer 1000 --payer-note "note"
+cargo run -- --base-url localhost:3000 spontaneous-send 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190 1000 --max-total-routing-fee-msat 100
+cargo run -- --base-url localhost:3000 unified-receive 1000 "description" 3600 --qr
+cargo run -- --base-url localhost:3000 unified-send uri
+cargo run -- --base-url localhost:3000 bolt11-probe invoice 1000
+cargo run -- --base-url localhost:3000 spontaneous-probe 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190 1000
 cargo run -- --base-url localhost:3000 node-balances
+cargo run -- --base-url localhost:3000 fee-estimates
 cargo run -- --base-url localhost:3000 payments-history
+cargo run -- --base-url localhost:3000 payments-history --label shop --external-id order-1
 cargo run -- --base-url localhost:3000 payment-details -p 12345678901234567890123456789012
+cargo run -- --base-url localhost:3000 payments-by-hash 0000000000000000000000000000000000000000000000000000000000000000
+cargo run -- --base-url localhost:3000 list-forwards
+cargo run -- --base-url localhost:3000 list-forwards --start 2024-01-01 --end 2024-02-01 --offset 10 --limit 10
+cargo run -- --base-url localhost:3000 export --start 2024-01-01 --end 2024-02-01
+cargo run -- --base-url localhost:3000 export --format jsonl --output export.jsonl
+cargo run -- --base-url localhost:3000 pending-approvals
+cargo run -- --base-url localhost:3000 approve-payment 18f2a3b4c5d6e7f80001 --approval-token secret
+cargo run -- --base-url localhost:3000 reject-payment 18f2a3b4c5d6e7f80001 --approval-token secret
+cargo run -- --base-url localhost:3000 audit-log --offset 10 --limit 10
+cargo run -- --base-url localhost:3000 verify-audit-log
 cargo run -- --base-url localhost:3000 list-channels
+cargo run -- --base-url localhost:3000 list-closed-channels
 cargo run -- --base-url localhost:3000 open-channel --node-id 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190 --address localhost:3042 --channel-amount-sats 1000000 --announce-channel
 cargo run -- --base-url localhost:3000 close-channel --user-channel-id 1234567890123456 --counterparty-node-id 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190
 cargo run -- --base-url localhost:3000 force-close-channel --user-channel-id 1234567890123456 --counterparty-node-id 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190
+cargo run -- --base-url localhost:3000 close-channel --all-channels --counterparty-node-id 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190
+cargo run -- --base-url localhost:3000 update-channel-config --user-channel-id 1234567890123456 --counterparty-node-id 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190 --forwarding-fee-base-msat 1000 --cltv-expiry-delta 144
+cargo run -- --base-url localhost:3000 graph-channels --limit 10
+cargo run -- --base-url localhost:3000 graph-channel 1234567890
+cargo run -- --base-url localhost:3000 graph-nodes --offset 10 --limit 10
+cargo run -- --base-url localhost:3000 graph-node 027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190
 
 popd
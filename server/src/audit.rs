@@ -0,0 +1,280 @@
+use std::fmt::Debug;
+use std::io;
+
+use ldk_node::bitcoin::hashes::{sha256, Hash};
+use protos::{
+	ApprovePaymentRequest, Bolt11CancelRequest, Bolt11ClaimRequest, Bolt11FailRequest,
+	Bolt11ReceiveForHashRequest, Bolt11ReceiveRequest, Bolt11SendRequest, Bolt12SendRequest,
+	CloseChannelRequest, ForceCloseChannelRequest, OnchainReceiveRequest, OnchainSendRequest,
+	OpenChannelRequest, RejectPaymentRequest, SignMessageRequest, SpontaneousSendRequest,
+	UnifiedReceiveRequest, UnifiedSendRequest, UpdateChannelConfigRequest,
+};
+
+use crate::store::{AuditEntry, JsonLinesLog};
+use crate::utils;
+
+/// The `prev_hash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// The value recorded in place of sensitive fields.
+const REDACTED: &str = "<redacted>";
+
+/// An append-only log of state-changing API calls, in which each entry commits to the previous
+/// one, so that modifying or removing an entry breaks the chain of hashes after it.
+///
+/// Every call is recorded twice: once before it is handled, so that no call goes unrecorded, and
+/// once more with its outcome.
+pub(crate) struct AuditLog {
+	entries: JsonLinesLog<AuditEntry>,
+}
+
+/// The result of checking the chain of hashes of the audit log.
+pub(crate) struct Verification {
+	pub(crate) entry_count: u64,
+	pub(crate) head_hash: String,
+	/// The sequence number of the first entry which doesn't match its hash or predecessor.
+	pub(crate) first_invalid_sequence: Option<u64>,
+}
+
+impl AuditLog {
+	pub(crate) fn new(entries: JsonLinesLog<AuditEntry>) -> Self {
+		Self { entries }
+	}
+
+	/// Records a call before it is handled, returning the sequence number of its entry.
+	pub(crate) fn record_call<T: AuditedRequest>(
+		&self, identity: &str, path: &str, request: &T,
+	) -> io::Result<u64> {
+		self.append(identity, path, format!("{:?}", request.redacted()), None, None)
+	}
+
+	/// Records the outcome of the call recorded under `call_sequence`.
+	pub(crate) fn record_outcome(
+		&self, identity: &str, path: &str, call_sequence: u64, error: Option<String>,
+	) -> io::Result<u64> {
+		self.append(identity, path, String::new(), Some(call_sequence), error)
+	}
+
+	fn append(
+		&self, identity: &str, path: &str, request: String, outcome_of: Option<u64>,
+		error: Option<String>,
+	) -> io::Result<u64> {
+		self.entries.append_with(|prev_entry| {
+			let mut entry = AuditEntry {
+				sequence: prev_entry.map_or(0, |prev_entry| prev_entry.sequence + 1),
+				timestamp: utils::unix_timestamp(),
+				identity: identity.to_string(),
+				path: path.to_string(),
+				request,
+				outcome_of,
+				error,
+				prev_hash: prev_entry
+					.map_or_else(|| GENESIS_HASH.to_string(), |prev_entry| prev_entry.hash.clone()),
+				hash: String::new(),
+			};
+			entry.hash = entry_hash(&entry);
+			entry
+		})
+	}
+
+	/// Returns the entries in order, skipping `offset` entries and returning at most `limit`.
+	pub(crate) fn list(&self, offset: usize, limit: usize) -> Vec<AuditEntry> {
+		self.entries.list_entries().into_iter().skip(offset).take(limit).map(|(_, e)| e).collect()
+	}
+
+	pub(crate) fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub(crate) fn verify(&self) -> Verification {
+		verify_entries(self.entries.list_entries().into_iter().map(|(_, entry)| entry).collect())
+	}
+}
+
+fn verify_entries(entries: Vec<AuditEntry>) -> Verification {
+	let mut prev_hash = GENESIS_HASH.to_string();
+	let mut first_invalid_sequence = None;
+	for (sequence, entry) in entries.iter().enumerate() {
+		let is_valid = entry.sequence == sequence as u64
+			&& entry.prev_hash == prev_hash
+			&& entry.hash == entry_hash(entry);
+		if !is_valid {
+			first_invalid_sequence = Some(sequence as u64);
+			break;
+		}
+		prev_hash = entry.hash.clone();
+	}
+	Verification {
+		entry_count: entries.len() as u64,
+		head_hash: entries.last().map_or_else(|| GENESIS_HASH.to_string(), |e| e.hash.clone()),
+		first_invalid_sequence,
+	}
+}
+
+fn entry_hash(entry: &AuditEntry) -> String {
+	let unhashed_entry = AuditEntry { hash: String::new(), ..entry.clone() };
+	let bytes = serde_json::to_vec(&unhashed_entry).expect("entries are serializable");
+	sha256::Hash::hash(&bytes).to_string()
+}
+
+/// A request recorded in the audit log.
+///
+/// Implemented explicitly for every audited request, so that adding one requires deciding which of
+/// its fields must not end up in the log.
+pub(crate) trait AuditedRequest: Debug + Clone {
+	/// The request with the values of sensitive fields replaced, as recorded in the log.
+	fn redacted(&self) -> Self {
+		self.clone()
+	}
+}
+
+impl AuditedRequest for Bolt11ClaimRequest {
+	fn redacted(&self) -> Self {
+		Self { preimage: REDACTED.to_string(), ..self.clone() }
+	}
+}
+
+impl AuditedRequest for ApprovePaymentRequest {
+	fn redacted(&self) -> Self {
+		Self { approval_token: REDACTED.to_string(), ..self.clone() }
+	}
+}
+
+impl AuditedRequest for RejectPaymentRequest {
+	fn redacted(&self) -> Self {
+		Self { approval_token: REDACTED.to_string(), ..self.clone() }
+	}
+}
+
+impl AuditedRequest for SignMessageRequest {}
+impl AuditedRequest for OnchainReceiveRequest {}
+impl AuditedRequest for OnchainSendRequest {}
+impl AuditedRequest for Bolt11ReceiveRequest {}
+impl AuditedRequest for Bolt11ReceiveForHashRequest {}
+impl AuditedRequest for Bolt11FailRequest {}
+impl AuditedRequest for Bolt11CancelRequest {}
+impl AuditedRequest for Bolt11SendRequest {}
+impl AuditedRequest for Bolt12SendRequest {}
+impl AuditedRequest for SpontaneousSendRequest {}
+impl AuditedRequest for UnifiedReceiveRequest {}
+impl AuditedRequest for UnifiedSendRequest {}
+impl AuditedRequest for OpenChannelRequest {}
+impl AuditedRequest for CloseChannelRequest {}
+impl AuditedRequest for ForceCloseChannelRequest {}
+impl AuditedRequest for UpdateChannelConfigRequest {}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+	use std::path::PathBuf;
+
+	use super::*;
+
+	fn audit_log(name: &str) -> (AuditLog, PathBuf) {
+		let path =
+			std::env::temp_dir().join(format!("audit_log_{}_{}.jsonl", name, std::process::id()));
+		let _ = fs::remove_file(&path);
+		(AuditLog::new(JsonLinesLog::open(path.clone()).unwrap()), path)
+	}
+
+	fn record_calls(audit_log: &AuditLog, count: u64) {
+		for i in 0..count {
+			let request = OnchainSendRequest { amount_sats: Some(i), ..Default::default() };
+			let sequence =
+				audit_log.record_call("127.0.0.1:1234", "/onchain/send", &request).unwrap();
+			audit_log.record_outcome("127.0.0.1:1234", "/onchain/send", sequence, None).unwrap();
+		}
+	}
+
+	#[test]
+	fn test_entries_are_chained() {
+		let (audit_log, path) = audit_log("chained");
+		let request = SignMessageRequest { message: b"hello".to_vec() };
+		let call_sequence = audit_log.record_call("127.0.0.1:1234", "/sign", &request).unwrap();
+		let error = Some("failed".to_string());
+		audit_log.record_outcome("127.0.0.1:1234", "/sign", call_sequence, error.clone()).unwrap();
+
+		let entries = audit_log.list(0, 10);
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].prev_hash, GENESIS_HASH);
+		assert_eq!(entries[0].request, format!("{:?}", request));
+		assert_eq!(entries[0].outcome_of, None);
+		assert_eq!(entries[1].prev_hash, entries[0].hash);
+		assert_eq!(entries[1].outcome_of, Some(call_sequence));
+		assert_eq!(entries[1].error, error);
+
+		let verification = audit_log.verify();
+		assert_eq!(verification.entry_count, 2);
+		assert_eq!(verification.head_hash, entries[1].hash);
+		assert_eq!(verification.first_invalid_sequence, None);
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_entries_are_chained_across_restarts() {
+		let (audit_log, path) = audit_log("restart");
+		record_calls(&audit_log, 2);
+		drop(audit_log);
+
+		let audit_log = AuditLog::new(JsonLinesLog::open(path.clone()).unwrap());
+		record_calls(&audit_log, 1);
+		assert_eq!(audit_log.len(), 6);
+		assert_eq!(audit_log.list(4, 10)[0].sequence, 4);
+		assert_eq!(audit_log.verify().first_invalid_sequence, None);
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_verify_detects_modified_entry() {
+		let (audit_log, path) = audit_log("modified");
+		record_calls(&audit_log, 2);
+		let mut entries = audit_log.list(0, 10);
+		entries[1].error = Some("forged".to_string());
+		assert_eq!(verify_entries(entries).first_invalid_sequence, Some(1));
+
+		// Recomputing the hash of the modified entry breaks the link to its successor instead.
+		let mut entries = audit_log.list(0, 10);
+		entries[1].error = Some("forged".to_string());
+		entries[1].hash = entry_hash(&entries[1]);
+		assert_eq!(verify_entries(entries).first_invalid_sequence, Some(2));
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_verify_detects_removed_entry() {
+		let (audit_log, path) = audit_log("removed");
+		record_calls(&audit_log, 2);
+		let mut entries = audit_log.list(0, 10);
+		entries.remove(0);
+		assert_eq!(verify_entries(entries).first_invalid_sequence, Some(0));
+
+		let mut entries = audit_log.list(0, 10);
+		entries.remove(2);
+		assert_eq!(verify_entries(entries).first_invalid_sequence, Some(2));
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_redacts_sensitive_fields() {
+		let request = Bolt11ClaimRequest {
+			payment_hash: "ab".repeat(32),
+			preimage: "cd".repeat(32),
+			claimable_amount_msat: Some(1000),
+		};
+		let summary = format!("{:?}", request.redacted());
+		assert!(!summary.contains(&"cd".repeat(32)));
+		assert!(summary.contains(REDACTED));
+		assert!(summary.contains(&"ab".repeat(32)));
+		assert!(summary.contains("claimable_amount_msat: Some(1000)"));
+
+		let request =
+			ApprovePaymentRequest { id: "1".to_string(), approval_token: "secret".to_string() };
+		let summary = format!("{:?}", request.redacted());
+		assert!(!summary.contains("secret"));
+		assert!(summary.contains(REDACTED));
+
+		let request =
+			RejectPaymentRequest { id: "1".to_string(), approval_token: "secret".to_string() };
+		assert!(!format!("{:?}", request.redacted()).contains("secret"));
+	}
+}
@@ -1,8 +1,11 @@
 use std::net::SocketAddr;
 use std::str::FromStr;
 
+use ldk_node::bitcoin::bip32::Xpub;
+use ldk_node::bitcoin::secp256k1::PublicKey;
+use ldk_node::bitcoin::{Address, NetworkKind};
 use ldk_node::lightning::ln::msgs::SocketAddress;
-use ldk_node::{bitcoin::Network, LogLevel};
+use ldk_node::{bitcoin::Network, logger::LogLevel};
 use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq, Eq, Debug)]
@@ -13,6 +16,76 @@ pub struct Config {
 	pub network: Network,
 	pub rest_service_addr: SocketAddr,
 	pub storage_dir_path: String,
+	pub inbound_channels: InboundChannelsConfig,
+	pub gossip_source: GossipSourceConfig,
+	pub auto_sweep: Option<AutoSweepConfig>,
+	pub spending_policy: SpendingPolicyConfig,
+}
+
+/// Where the node sources its view of the network graph from.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub enum GossipSourceConfig {
+	/// Sync the network graph via the peer-to-peer gossip protocol.
+	#[default]
+	P2p,
+	/// Sync the network graph from the Rapid Gossip Sync server at the given URL.
+	Rgs(String),
+}
+
+/// Policy applied to channels opened to us by our peers.
+///
+/// LDK Node accepts inbound channels automatically and offers no way to reject an open request, so
+/// unwanted channels are accepted and then cooperatively closed once they are pending. The funding
+/// transaction is still broadcast, and the counterparty pays for both it and the closing
+/// transaction.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct InboundChannelsConfig {
+	/// Inbound channels smaller than this are closed as soon as they are pending.
+	pub close_channels_below_sats: u64,
+	/// If set, unannounced inbound channels are closed as soon as they are pending.
+	pub close_unannounced_channels: bool,
+	/// Peers from which we accept inbound zero-confirmation channels.
+	pub trusted_peers_0conf: Vec<PublicKey>,
+}
+
+/// Policy for automatically sweeping surplus on-chain funds to a cold wallet.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct AutoSweepConfig {
+	/// Once our spendable on-chain balance exceeds this, the surplus above it is swept.
+	pub threshold_sats: u64,
+	/// Surpluses smaller than this are left in the wallet until they grow, so that we don't pay
+	/// fees for sweeping dust.
+	pub min_sweep_amount_sats: u64,
+	pub destination: SweepDestination,
+	/// Sweeps are deferred while the estimated fee rate is above this.
+	pub max_fee_rate_sat_per_vb: u64,
+	/// The number of blocks within which sweeps should confirm, used to estimate their fee rate.
+	pub confirmation_target_blocks: u32,
+	pub check_interval_secs: u64,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum SweepDestination {
+	Address(Address),
+	/// Each sweep goes to a fresh P2WPKH address derived from the external chain (`0/*`) of the
+	/// given extended public key.
+	Xpub(Xpub),
+}
+
+/// Limits and approval rules applied to outgoing payments.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct SpendingPolicyConfig {
+	pub max_payment_sats: Option<u64>,
+	pub max_hourly_sats: Option<u64>,
+	pub max_daily_sats: Option<u64>,
+	/// If non-empty, payments may only be sent to these node ids and on-chain addresses.
+	pub allowed_destinations: Vec<String>,
+	pub denied_destinations: Vec<String>,
+	/// Payments above this amount are parked until they are approved. If set to zero, all
+	/// payments need approval.
+	pub require_approval_above_sats: Option<u64>,
+	/// The token needed to approve or reject parked payments.
+	pub approval_token: Option<String>,
 }
 
 impl From<JsonConfig> for Config {
@@ -28,13 +101,77 @@ impl From<JsonConfig> for Config {
 			"error" => LogLevel::Error,
 			_ => panic!("Unsupported log level: {}", json_config.log_level),
 		};
+		let inbound_channels = json_config
+			.inbound_channels
+			.map(|c| InboundChannelsConfig {
+				close_channels_below_sats: c.close_channels_below_sats.unwrap_or(0),
+				close_unannounced_channels: c.close_unannounced_channels.unwrap_or(false),
+				trusted_peers_0conf: c
+					.trusted_peers_0conf
+					.unwrap_or_default()
+					.iter()
+					.map(|peer| PublicKey::from_str(peer).unwrap())
+					.collect(),
+			})
+			.unwrap_or_default();
+		let gossip_source = match json_config.rgs_server_url {
+			Some(rgs_server_url) => GossipSourceConfig::Rgs(rgs_server_url),
+			None => GossipSourceConfig::P2p,
+		};
+		let network = json_config.network;
+		let auto_sweep = json_config.auto_sweep.map(|c| {
+			let destination = match (c.destination_address, c.destination_xpub) {
+				(Some(address), None) => SweepDestination::Address(
+					Address::from_str(&address).unwrap().require_network(network).unwrap(),
+				),
+				(None, Some(xpub)) => {
+					let xpub = Xpub::from_str(&xpub).unwrap();
+					if xpub.network != NetworkKind::from(network) {
+						panic!("Auto-sweep xpub is for the wrong network: {}", xpub);
+					}
+					SweepDestination::Xpub(xpub)
+				},
+				_ => panic!(
+					"Exactly one of destination_address and destination_xpub must be set for auto_sweep"
+				),
+			};
+			AutoSweepConfig {
+				threshold_sats: c.threshold_sats,
+				min_sweep_amount_sats: c.min_sweep_amount_sats.unwrap_or(10_000),
+				destination,
+				max_fee_rate_sat_per_vb: c.max_fee_rate_sat_per_vb,
+				confirmation_target_blocks: c.confirmation_target_blocks.unwrap_or(6),
+				check_interval_secs: c.check_interval_secs.unwrap_or(600),
+			}
+		});
+		let spending_policy = json_config
+			.spending_policy
+			.map(|c| {
+				if c.require_approval_above_sats.is_some() && c.approval_token.is_none() {
+					panic!("An approval_token must be set if spending_policy requires approval");
+				}
+				SpendingPolicyConfig {
+					max_payment_sats: c.max_payment_sats,
+					max_hourly_sats: c.max_hourly_sats,
+					max_daily_sats: c.max_daily_sats,
+					allowed_destinations: c.allowed_destinations.unwrap_or_default(),
+					denied_destinations: c.denied_destinations.unwrap_or_default(),
+					require_approval_above_sats: c.require_approval_above_sats,
+					approval_token: c.approval_token,
+				}
+			})
+			.unwrap_or_default();
 		Config {
 			esplora_server_url: json_config.esplora_server_url,
 			listening_addr,
 			log_level,
-			network: json_config.network,
+			network,
 			rest_service_addr,
 			storage_dir_path: json_config.storage_dir_path,
+			inbound_channels,
+			gossip_source,
+			auto_sweep,
+			spending_policy,
 		}
 	}
 }
@@ -47,4 +184,37 @@ pub struct JsonConfig {
 	network: Network,
 	rest_service_addr: String,
 	storage_dir_path: String,
+	inbound_channels: Option<JsonInboundChannelsConfig>,
+	rgs_server_url: Option<String>,
+	auto_sweep: Option<JsonAutoSweepConfig>,
+	spending_policy: Option<JsonSpendingPolicyConfig>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonInboundChannelsConfig {
+	close_channels_below_sats: Option<u64>,
+	close_unannounced_channels: Option<bool>,
+	trusted_peers_0conf: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonAutoSweepConfig {
+	threshold_sats: u64,
+	min_sweep_amount_sats: Option<u64>,
+	destination_address: Option<String>,
+	destination_xpub: Option<String>,
+	max_fee_rate_sat_per_vb: u64,
+	confirmation_target_blocks: Option<u32>,
+	check_interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct JsonSpendingPolicyConfig {
+	max_payment_sats: Option<u64>,
+	max_hourly_sats: Option<u64>,
+	max_daily_sats: Option<u64>,
+	allowed_destinations: Option<Vec<String>>,
+	denied_destinations: Option<Vec<String>>,
+	require_approval_above_sats: Option<u64>,
+	approval_token: Option<String>,
 }
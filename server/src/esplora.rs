@@ -0,0 +1,128 @@
+use core::str::FromStr;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use ldk_node::bitcoin::{FeeRate, OutPoint, Txid};
+use ldk_node::lightning::chain::chaininterface::FEERATE_FLOOR_SATS_PER_KW;
+use serde::Deserialize;
+
+use crate::utils;
+
+/// How often we look up the spend of an output before giving up. The spending transaction may
+/// take a while to propagate to the Esplora server, or to be broadcast at all if our counterparty
+/// is offline, so the interval between lookups doubles up to a maximum, spanning about a day.
+const OUTSPEND_LOOKUP_ATTEMPTS: u32 = 32;
+const OUTSPEND_LOOKUP_INITIAL_INTERVAL: Duration = Duration::from_secs(30);
+const OUTSPEND_LOOKUP_MAX_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// How often we refresh our fee rate estimates, matching how often the node refreshes its own.
+const FEE_RATE_UPDATE_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Deserialize)]
+struct Outspend {
+	spent: bool,
+	txid: Option<String>,
+}
+
+/// Looks up the transaction spending the given output via the Esplora API.
+pub(crate) async fn find_spending_txid(
+	esplora_server_url: &str, outpoint: OutPoint,
+) -> Option<Txid> {
+	let url = format!(
+		"{}/tx/{}/outspend/{}",
+		esplora_server_url.trim_end_matches('/'),
+		outpoint.txid,
+		outpoint.vout
+	);
+	let mut interval = OUTSPEND_LOOKUP_INITIAL_INTERVAL;
+	for _ in 0..OUTSPEND_LOOKUP_ATTEMPTS {
+		match get_outspend(&url).await {
+			Ok(Outspend { spent: true, txid: Some(txid) }) => return Txid::from_str(&txid).ok(),
+			Ok(_) => {},
+			Err(e) => eprintln!("Failed to look up spend of {}: {}", outpoint, e),
+		}
+		tokio::time::sleep(interval).await;
+		interval = (interval * 2).min(OUTSPEND_LOOKUP_MAX_INTERVAL);
+	}
+	None
+}
+
+async fn get_outspend(url: &str) -> Result<Outspend, Box<dyn std::error::Error + Send + Sync>> {
+	let body = reqwest::get(url).await?.error_for_status()?.text().await?;
+	Ok(serde_json::from_str(&body)?)
+}
+
+/// The fee rate estimates of the Esplora server, keyed by the number of blocks within which a
+/// transaction paying them is expected to confirm.
+///
+/// The node sources its own fee rate estimates from the same server, but doesn't expose them.
+#[derive(Default)]
+pub(crate) struct FeeRateEstimates {
+	estimates: RwLock<Option<(u64, HashMap<u32, f64>)>>,
+}
+
+impl FeeRateEstimates {
+	/// Returns the estimated fee rate for confirmation within the given number of blocks, or
+	/// `None` if no estimates have been retrieved yet.
+	///
+	/// Like the node, we fall back to 1 sat/vB if the server has no estimate for the target, and
+	/// never go below the minimum relay fee rate.
+	pub(crate) fn fee_rate(&self, target_blocks: u32) -> Option<FeeRate> {
+		let estimates = self.estimates.read().unwrap();
+		let (_, estimates) = estimates.as_ref()?;
+		let sat_per_vb = estimates
+			.iter()
+			.filter(|(target, _)| **target <= target_blocks)
+			.max_by_key(|(target, _)| **target)
+			.map_or(1.0, |(_, sat_per_vb)| sat_per_vb.max(1.0));
+		let sat_per_kwu = ((sat_per_vb * 250.0) as u64).max(FEERATE_FLOOR_SATS_PER_KW as u64);
+		Some(FeeRate::from_sat_per_kwu(sat_per_kwu))
+	}
+
+	/// Returns the estimates as retrieved from the server, ordered by confirmation target.
+	pub(crate) fn list(&self) -> Vec<(u32, f64)> {
+		let estimates = self.estimates.read().unwrap();
+		let mut estimates = estimates
+			.as_ref()
+			.map(|(_, estimates)| estimates.iter().map(|(t, r)| (*t, *r)).collect::<Vec<_>>())
+			.unwrap_or_default();
+		estimates.sort_unstable_by_key(|(target_blocks, _)| *target_blocks);
+		estimates
+	}
+
+	/// The time of the last successful update, in seconds since the UNIX epoch.
+	pub(crate) fn latest_update_timestamp(&self) -> Option<u64> {
+		self.estimates.read().unwrap().as_ref().map(|(timestamp, _)| *timestamp)
+	}
+
+	fn set(&self, estimates: HashMap<u32, f64>) {
+		*self.estimates.write().unwrap() = Some((utils::unix_timestamp(), estimates));
+	}
+}
+
+/// Periodically retrieves the fee rate estimates of the Esplora server.
+pub(crate) async fn run_fee_rate_updates(
+	esplora_server_url: String, fee_rate_estimates: Arc<FeeRateEstimates>,
+) {
+	let url = format!("{}/fee-estimates", esplora_server_url.trim_end_matches('/'));
+	let mut interval = tokio::time::interval(FEE_RATE_UPDATE_INTERVAL);
+	loop {
+		interval.tick().await;
+		match get_fee_estimates(&url).await {
+			Ok(estimates) => fee_rate_estimates.set(estimates),
+			Err(e) => eprintln!("Failed to update fee rate estimates: {}", e),
+		}
+	}
+}
+
+async fn get_fee_estimates(
+	url: &str,
+) -> Result<HashMap<u32, f64>, Box<dyn std::error::Error + Send + Sync>> {
+	let body = reqwest::get(url).await?.error_for_status()?.text().await?;
+	let estimates: HashMap<String, f64> = serde_json::from_str(&body)?;
+	Ok(estimates
+		.into_iter()
+		.filter_map(|(target, sat_per_vb)| Some((target.parse().ok()?, sat_per_vb)))
+		.collect())
+}
@@ -0,0 +1,156 @@
+use serde::Serialize;
+
+/// The columns of exported records, in order. These are part of the export format and must stay
+/// stable, so new columns may only ever be appended.
+const CSV_COLUMNS: [&str; 9] = [
+	"timestamp",
+	"category",
+	"id",
+	"amount_msat",
+	"fee_paid_msat",
+	"fee_earned_msat",
+	"channel_id",
+	"external_id",
+	"memo",
+];
+
+/// A single settled entry affecting our balance, as exported for accounting.
+#[derive(Serialize)]
+pub(crate) struct ExportRecord {
+	/// The unix timestamp at which the entry settled.
+	pub(crate) timestamp: u64,
+	pub(crate) category: ExportCategory,
+	/// The payment id, txid or forward index the entry refers to.
+	pub(crate) id: String,
+	/// The effect of the entry on our balance excluding fees. Negative for outgoing funds.
+	pub(crate) amount_msat: i64,
+	pub(crate) fee_paid_msat: u64,
+	pub(crate) fee_earned_msat: u64,
+	pub(crate) channel_id: Option<String>,
+	pub(crate) external_id: Option<String>,
+	pub(crate) memo: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum ExportCategory {
+	PaymentReceived,
+	PaymentSent,
+	Forward,
+	OnchainReceive,
+	OnchainSend,
+	ChannelOpen,
+	ChannelClose,
+}
+
+impl ExportCategory {
+	fn as_str(&self) -> &'static str {
+		match self {
+			ExportCategory::PaymentReceived => "payment_received",
+			ExportCategory::PaymentSent => "payment_sent",
+			ExportCategory::Forward => "forward",
+			ExportCategory::OnchainReceive => "onchain_receive",
+			ExportCategory::OnchainSend => "onchain_send",
+			ExportCategory::ChannelOpen => "channel_open",
+			ExportCategory::ChannelClose => "channel_close",
+		}
+	}
+}
+
+pub(crate) fn to_csv(records: &[ExportRecord]) -> String {
+	let mut csv = CSV_COLUMNS.join(",");
+	csv.push('\n');
+	for record in records {
+		let fields = [
+			record.timestamp.to_string(),
+			record.category.as_str().to_string(),
+			escape_csv_field(&record.id),
+			record.amount_msat.to_string(),
+			record.fee_paid_msat.to_string(),
+			record.fee_earned_msat.to_string(),
+			escape_csv_field(record.channel_id.as_deref().unwrap_or_default()),
+			escape_csv_field(record.external_id.as_deref().unwrap_or_default()),
+			escape_csv_field(record.memo.as_deref().unwrap_or_default()),
+		];
+		csv.push_str(&fields.join(","));
+		csv.push('\n');
+	}
+	csv
+}
+
+pub(crate) fn to_json_lines(records: &[ExportRecord]) -> String {
+	let mut json_lines = String::new();
+	for record in records {
+		json_lines.push_str(&serde_json::to_string(record).expect("records are serializable"));
+		json_lines.push('\n');
+	}
+	json_lines
+}
+
+/// Quotes the field as per RFC 4180 if it contains a delimiter, quote or line break.
+fn escape_csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record(category: ExportCategory, amount_msat: i64, memo: Option<&str>) -> ExportRecord {
+		ExportRecord {
+			timestamp: 1700000000,
+			category,
+			id: "a1b2".to_string(),
+			amount_msat,
+			fee_paid_msat: 12,
+			fee_earned_msat: 0,
+			channel_id: None,
+			external_id: Some("order-1".to_string()),
+			memo: memo.map(str::to_string),
+		}
+	}
+
+	#[test]
+	fn test_escape_csv_field() {
+		assert_eq!(escape_csv_field("plain memo"), "plain memo");
+		assert_eq!(escape_csv_field(""), "");
+		assert_eq!(escape_csv_field("coffee, tea"), "\"coffee, tea\"");
+		assert_eq!(escape_csv_field("the \"good\" one"), "\"the \"\"good\"\" one\"");
+		assert_eq!(escape_csv_field("two\nlines"), "\"two\nlines\"");
+		assert_eq!(escape_csv_field("carriage\rreturn"), "\"carriage\rreturn\"");
+	}
+
+	#[test]
+	fn test_to_csv() {
+		let records = [
+			record(ExportCategory::PaymentSent, -1000, Some("coffee, \"large\"")),
+			record(ExportCategory::OnchainReceive, 5000, None),
+		];
+		assert_eq!(
+			to_csv(&records),
+			"timestamp,category,id,amount_msat,fee_paid_msat,fee_earned_msat,channel_id,external_id,memo\n\
+			 1700000000,payment_sent,a1b2,-1000,12,0,,order-1,\"coffee, \"\"large\"\"\"\n\
+			 1700000000,onchain_receive,a1b2,5000,12,0,,order-1,\n"
+		);
+	}
+
+	#[test]
+	fn test_to_csv_without_records() {
+		assert_eq!(to_csv(&[]), format!("{}\n", CSV_COLUMNS.join(",")));
+	}
+
+	#[test]
+	fn test_to_json_lines() {
+		let records = [record(ExportCategory::ChannelClose, 0, None)];
+		assert_eq!(
+			to_json_lines(&records),
+			"{\"timestamp\":1700000000,\"category\":\"channel_close\",\"id\":\"a1b2\",\
+			 \"amount_msat\":0,\"fee_paid_msat\":12,\"fee_earned_msat\":0,\"channel_id\":null,\
+			 \"external_id\":\"order-1\",\"memo\":null}\n"
+		);
+	}
+}
@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use ldk_node::bitcoin::hashes::hex::FromHex;
+use ldk_node::bitcoin::hashes::{sha256, Hash};
+use prost::Message;
+
+use crate::service::HandlerError;
+use crate::store::{IdempotentResponse, JsonFileStore};
+use crate::utils;
+
+pub(crate) const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// How long responses are kept. Requests repeated after this are executed again.
+const RESPONSE_RETENTION_SECS: u64 = 24 * 60 * 60;
+
+/// Ensures requests carrying the same idempotency key are executed at most once, by persisting
+/// the response to the first successful one.
+pub(crate) struct IdempotencyStore {
+	responses: JsonFileStore<IdempotentResponse>,
+	/// Keys of requests currently being executed.
+	in_flight: Mutex<HashSet<String>>,
+}
+
+impl IdempotencyStore {
+	pub(crate) fn new(responses: JsonFileStore<IdempotentResponse>) -> Self {
+		Self { responses, in_flight: Mutex::new(HashSet::new()) }
+	}
+
+	/// Executes the request via `handler`, unless a request with the same key was already
+	/// executed successfully, in which case its response is returned instead.
+	///
+	/// Failed requests aren't remembered, so they may be retried with the same key. The request
+	/// is recorded before it is executed and isn't executed if that fails. If its response or
+	/// result can't be persisted, repeating it fails rather than executing it again.
+	pub(crate) fn execute<T: Message, R: Message + Default>(
+		&self, key: &str, path: &str, request: T,
+		handler: impl FnOnce(T) -> Result<R, HandlerError>,
+	) -> Result<R, HandlerError> {
+		let request_hash = sha256::Hash::hash(&request.encode_to_vec()).to_string();
+		if let Some(response) = self.get_unexpired(key) {
+			return replay(response, path, &request_hash);
+		}
+
+		if !self.in_flight.lock().unwrap().insert(key.to_string()) {
+			return Err(HandlerError::Conflict(format!(
+				"A request with idempotency key {} is already in progress",
+				key
+			)));
+		}
+		let _in_flight = InFlightGuard { in_flight: &self.in_flight, key };
+		// The request may have completed since we first checked.
+		if let Some(response) = self.get_unexpired(key) {
+			return replay(response, path, &request_hash);
+		}
+
+		let now = utils::unix_timestamp();
+		let mut record = IdempotentResponse {
+			path: path.to_string(),
+			request_hash,
+			response: None,
+			created_at: now,
+		};
+		self.responses
+			.retain(|_, stored_response| !is_expired(stored_response, now))
+			.and_then(|()| self.responses.insert(key.to_string(), record.clone()))
+			.map_err(|e| {
+				eprintln!("Failed to record request with idempotency key {}: {}", key, e);
+				ldk_node::NodeError::PersistenceFailed
+			})?;
+
+		match handler(request) {
+			Ok(response) => {
+				record.response = Some(utils::to_hex(&response.encode_to_vec()));
+				self.responses.insert(key.to_string(), record).map_err(|e| {
+					eprintln!("Failed to persist response for idempotency key {}: {}", key, e);
+					ldk_node::NodeError::PersistenceFailed
+				})?;
+				Ok(response)
+			},
+			// The request may have taken effect if only persisting its result failed, so it must
+			// not be repeated.
+			Err(e @ HandlerError::Node(ldk_node::NodeError::PersistenceFailed)) => Err(e),
+			Err(e) => {
+				if let Err(remove_error) = self.responses.remove(key) {
+					eprintln!(
+						"Failed to release idempotency key {} of failed request: {}",
+						key, remove_error
+					);
+				}
+				Err(e)
+			},
+		}
+	}
+
+	fn get_unexpired(&self, key: &str) -> Option<IdempotentResponse> {
+		let response = self.responses.get(key)?;
+		(!is_expired(&response, utils::unix_timestamp())).then_some(response)
+	}
+}
+
+fn is_expired(response: &IdempotentResponse, now: u64) -> bool {
+	response.created_at + RESPONSE_RETENTION_SECS <= now
+}
+
+fn replay<R: Message + Default>(
+	response: IdempotentResponse, path: &str, request_hash: &str,
+) -> Result<R, HandlerError> {
+	if response.path != path || response.request_hash != request_hash {
+		return Err(HandlerError::InvalidRequest(
+			"Idempotency key was already used for a different request".to_string(),
+		));
+	}
+	// The request was recorded, but either its response couldn't be persisted or the server
+	// stopped while executing it, so we can't tell whether it succeeded.
+	let response = response.response.ok_or_else(|| {
+		HandlerError::Conflict(
+			"The outcome of the earlier request with this idempotency key is unknown".to_string(),
+		)
+	})?;
+	let bytes =
+		Vec::<u8>::from_hex(&response).map_err(|_| ldk_node::NodeError::PersistenceFailed)?;
+	Ok(R::decode(&*bytes).map_err(|_| ldk_node::NodeError::PersistenceFailed)?)
+}
+
+/// Releases the key of an in-flight request once it completed, even if its handler panicked.
+struct InFlightGuard<'a> {
+	in_flight: &'a Mutex<HashSet<String>>,
+	key: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+	fn drop(&mut self) {
+		self.in_flight.lock().unwrap().remove(self.key);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::cell::Cell;
+	use std::fs;
+
+	use protos::{Bolt11SendRequest, Bolt11SendResponse};
+
+	use super::*;
+
+	const PATH: &str = "/bolt11/send";
+
+	fn idempotency_store(name: &str) -> IdempotencyStore {
+		let path = std::env::temp_dir().join(format!(
+			"idempotent_responses_{}_{}.json",
+			name,
+			std::process::id()
+		));
+		let _ = fs::remove_file(&path);
+		IdempotencyStore::new(JsonFileStore::open(path).unwrap())
+	}
+
+	fn request(invoice: &str) -> Bolt11SendRequest {
+		Bolt11SendRequest { invoice: invoice.to_string(), ..Default::default() }
+	}
+
+	fn response(payment_id: u8) -> Bolt11SendResponse {
+		Bolt11SendResponse {
+			payment_id: Some(protos::PaymentId { data: vec![payment_id; 32] }),
+			approval_id: None,
+		}
+	}
+
+	#[test]
+	fn test_replays_response_to_repeated_request() {
+		let store = idempotency_store("replay");
+		let executions = Cell::new(0);
+		let execute = |payment_id| {
+			store.execute("key", PATH, request("lnbc1"), |_| {
+				executions.set(executions.get() + 1);
+				Ok(response(payment_id))
+			})
+		};
+		assert_eq!(execute(1).unwrap(), response(1));
+		assert_eq!(execute(2).unwrap(), response(1));
+		assert_eq!(executions.get(), 1);
+
+		// Requests under other keys are executed on their own.
+		let other = store.execute("other-key", PATH, request("lnbc1"), |_| Ok(response(3)));
+		assert_eq!(other.unwrap(), response(3));
+	}
+
+	#[test]
+	fn test_retries_failed_request() {
+		let store = idempotency_store("retry");
+		let result = store.execute("key", PATH, request("lnbc1"), |_| {
+			Err::<Bolt11SendResponse, _>(HandlerError::Node(
+				ldk_node::NodeError::PaymentSendingFailed,
+			))
+		});
+		assert!(result.is_err());
+		let result = store.execute("key", PATH, request("lnbc1"), |_| Ok(response(1)));
+		assert_eq!(result.unwrap(), response(1));
+	}
+
+	#[test]
+	fn test_rejects_key_reused_for_different_request() {
+		let store = idempotency_store("mismatch");
+		store.execute("key", PATH, request("lnbc1"), |_| Ok(response(1))).unwrap();
+
+		let result = store.execute(
+			"key",
+			PATH,
+			request("lnbc2"),
+			|_| -> Result<Bolt11SendResponse, HandlerError> {
+				panic!("Request with reused key was executed")
+			},
+		);
+		assert!(matches!(result, Err(HandlerError::InvalidRequest(_))));
+
+		let result = store.execute(
+			"key",
+			"/bolt12/send",
+			request("lnbc1"),
+			|_| -> Result<Bolt11SendResponse, HandlerError> {
+				panic!("Request with reused key was executed")
+			},
+		);
+		assert!(matches!(result, Err(HandlerError::InvalidRequest(_))));
+	}
+
+	#[test]
+	fn test_rejects_request_while_in_flight() {
+		let store = idempotency_store("in_flight");
+		let result = store.execute("key", PATH, request("lnbc1"), |_| {
+			// A request arriving while the first one is still being handled is turned away.
+			let concurrent = store.execute(
+				"key",
+				PATH,
+				request("lnbc1"),
+				|_| -> Result<Bolt11SendResponse, HandlerError> {
+					panic!("Request was executed twice")
+				},
+			);
+			assert!(matches!(concurrent, Err(HandlerError::Conflict(_))));
+			Ok(response(1))
+		});
+		assert_eq!(result.unwrap(), response(1));
+
+		// Once it completed, the key is released and the response replayed.
+		let result = store.execute("key", PATH, request("lnbc1"), |_| Ok(response(2)));
+		assert_eq!(result.unwrap(), response(1));
+	}
+
+	#[test]
+	fn test_executes_request_again_once_response_expired() {
+		let store = idempotency_store("expired");
+		store.execute("key", PATH, request("lnbc1"), |_| Ok(response(1))).unwrap();
+		let mut stored_response = store.responses.get("key").unwrap();
+		stored_response.created_at -= RESPONSE_RETENTION_SECS;
+		store.responses.insert("key".to_string(), stored_response).unwrap();
+
+		let result = store.execute("key", PATH, request("lnbc1"), |_| Ok(response(2)));
+		assert_eq!(result.unwrap(), response(2));
+		assert_eq!(store.responses.list().len(), 1);
+	}
+
+	#[test]
+	fn test_rejects_request_with_unknown_outcome() {
+		let store = idempotency_store("unknown_outcome");
+		// A request recorded without a response, as left behind by a crash while executing it.
+		let request_hash = sha256::Hash::hash(&request("lnbc1").encode_to_vec()).to_string();
+		let pending = IdempotentResponse {
+			path: PATH.to_string(),
+			request_hash,
+			response: None,
+			created_at: utils::unix_timestamp(),
+		};
+		store.responses.insert("key".to_string(), pending).unwrap();
+
+		let result = store.execute(
+			"key",
+			PATH,
+			request("lnbc1"),
+			|_| -> Result<Bolt11SendResponse, HandlerError> {
+				panic!("Request with unknown outcome was executed again")
+			},
+		);
+		assert!(matches!(result, Err(HandlerError::Conflict(_))));
+	}
+}
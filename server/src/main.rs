@@ -1,13 +1,35 @@
+mod audit;
 mod config;
+mod esplora;
+mod export;
+mod idempotency;
+mod payment_uri;
+mod policy;
+mod store;
+mod sweep;
 mod utils;
 
-use std::{path::Path, sync::Arc};
+use std::{path::Path, str::FromStr, sync::Arc};
 
+use crate::audit::AuditLog;
+use crate::config::{GossipSourceConfig, InboundChannelsConfig};
+use crate::esplora::FeeRateEstimates;
+use crate::idempotency::IdempotencyStore;
+use crate::policy::SpendingPolicy;
 use crate::service::NodeService;
+use crate::store::{
+	ChannelRecord, ClaimablePayment, ForwardedPayment, JsonFileStore, JsonLinesLog, JsonLinesMap,
+};
 use hyper::server::conn::http1;
 use hyper_util::rt::TokioIo;
-use ldk_node::{Builder, Config as LdkNodeConfig, Event};
+use ldk_node::bitcoin::secp256k1::PublicKey;
+use ldk_node::bitcoin::{OutPoint, Txid};
+use ldk_node::config::Config as LdkNodeConfig;
+use ldk_node::lightning::ln::types::ChannelId;
+use ldk_node::liquidity::LSPS2ServiceConfig;
+use ldk_node::{Builder, Event, LightningBalance, Node, UserChannelId};
 use tokio::net::TcpListener;
+use tokio::runtime::Runtime;
 use tokio::signal::unix::SignalKind;
 
 mod service;
@@ -22,14 +44,31 @@ fn main() {
 
 	let mut ldk_node_config = LdkNodeConfig::default();
 	let config = utils::read_config_from_json(Path::new(&args[1])).unwrap();
-	ldk_node_config.storage_dir_path = config.storage_dir_path;
-	ldk_node_config.log_level = config.log_level;
+	ldk_node_config.storage_dir_path = config.storage_dir_path.clone();
 	ldk_node_config.network = config.network;
 	ldk_node_config.listening_addresses = Some(vec![config.listening_addr.clone()]);
+	ldk_node_config.trusted_peers_0conf = config.inbound_channels.trusted_peers_0conf.clone();
 
 	let mut builder = Builder::from_config(ldk_node_config);
-	builder.set_esplora_server(config.esplora_server_url);
-	builder.set_liquidity_provider_lsps2([42u8; 32]);
+	builder.set_chain_source_esplora(config.esplora_server_url.clone(), None);
+	builder.set_filesystem_logger(None, Some(config.log_level));
+	match &config.gossip_source {
+		GossipSourceConfig::P2p => builder.set_gossip_source_p2p(),
+		GossipSourceConfig::Rgs(rgs_server_url) => {
+			builder.set_gossip_source_rgs(rgs_server_url.clone())
+		},
+	};
+	builder.set_liquidity_provider_lsps2(LSPS2ServiceConfig {
+		require_token: None,
+		advertise_service: false,
+		channel_opening_fee_ppm: 0,
+		channel_over_provisioning_ppm: 0,
+		min_channel_opening_fee_msat: 0,
+		min_channel_lifetime: 100,
+		max_client_to_self_delay: 1024,
+		min_payment_size_msat: 0,
+		max_payment_size_msat: 1_000_000_000,
+	});
 
 	let runtime =
 		Arc::new(tokio::runtime::Builder::new_multi_thread().enable_all().build().unwrap());
@@ -38,7 +77,63 @@ fn main() {
 	println!("Starting up...");
 	node.start_with_runtime(Arc::clone(&runtime)).unwrap();
 
-	println!("CONNECTION_STRING: {}@{}", node.node_id(), config.listening_addr.to_string());
+	let storage_dir_path = Path::new(&config.storage_dir_path);
+	let payment_failure_reasons = Arc::new(
+		JsonFileStore::open(storage_dir_path.join("payment_failure_reasons.json")).unwrap(),
+	);
+	let issued_invoices =
+		Arc::new(JsonFileStore::open(storage_dir_path.join("issued_invoices.json")).unwrap());
+	let claimable_payments =
+		Arc::new(JsonFileStore::open(storage_dir_path.join("claimable_payments.json")).unwrap());
+	let payment_metadata =
+		Arc::new(JsonFileStore::open(storage_dir_path.join("payment_metadata.json")).unwrap());
+	let forwarded_payments =
+		Arc::new(JsonLinesLog::open(storage_dir_path.join("forwarded_payments.jsonl")).unwrap());
+	let channel_history: Arc<JsonLinesMap<ChannelRecord>> =
+		Arc::new(JsonLinesMap::open(storage_dir_path.join("channel_history.jsonl")).unwrap());
+	// Look up the closing transactions we didn't find before the last shutdown.
+	for (key, record) in channel_history.list_entries() {
+		if record.closed_at.is_some() && record.closing_txid.is_none() {
+			if let Ok(user_channel_id) = u128::from_str(&key) {
+				record_closing_txid(
+					&runtime,
+					Arc::clone(&channel_history),
+					config.esplora_server_url.clone(),
+					UserChannelId(user_channel_id),
+				);
+			}
+		}
+	}
+	let spending_policy = Arc::new(SpendingPolicy::new(
+		config.spending_policy.clone(),
+		JsonFileStore::open(storage_dir_path.join("spends.json")).unwrap(),
+		JsonFileStore::open(storage_dir_path.join("parked_payments.json")).unwrap(),
+	));
+	let idempotency_store = Arc::new(IdempotencyStore::new(
+		JsonFileStore::open(storage_dir_path.join("idempotent_responses.json")).unwrap(),
+	));
+	let fee_rate_estimates = Arc::new(FeeRateEstimates::default());
+	runtime.spawn(esplora::run_fee_rate_updates(
+		config.esplora_server_url.clone(),
+		Arc::clone(&fee_rate_estimates),
+	));
+	let audit_log = Arc::new(AuditLog::new(
+		JsonLinesLog::open(storage_dir_path.join("audit_log.jsonl")).unwrap(),
+	));
+
+	if let Some(auto_sweep) = config.auto_sweep.clone() {
+		let sweep_log =
+			Arc::new(JsonLinesMap::open(storage_dir_path.join("sweep_log.jsonl")).unwrap());
+		runtime.spawn(sweep::run_auto_sweep(
+			Arc::clone(&node),
+			auto_sweep,
+			sweep_log,
+			Arc::clone(&fee_rate_estimates),
+			Arc::clone(&audit_log),
+		));
+	}
+
+	println!("CONNECTION_STRING: {}@{}", node.node_id(), config.listening_addr);
 	println!("FUNDING ADDRESS: {}", node.onchain_payment().new_address().unwrap());
 
 	runtime.block_on(async {
@@ -57,33 +152,172 @@ fn main() {
 			tokio::select! {
 				event = event_node.next_event_async() => {
 					match event {
-						Event::ChannelPending { channel_id, counterparty_node_id, .. } => {
+						Event::ChannelPending {
+							channel_id,
+							user_channel_id,
+							counterparty_node_id,
+							funding_txo,
+							..
+						} => {
 							println!(
 								"CHANNEL_PENDING: {} from counterparty {}",
 								channel_id, counterparty_node_id
 								);
+							let channel = event_node
+								.list_channels()
+								.into_iter()
+								.find(|c| c.user_channel_id == user_channel_id);
+							update_channel_record(&channel_history, &user_channel_id, |record| {
+								record.channel_id = channel_id.to_string();
+								record.counterparty_node_id = Some(counterparty_node_id.to_string());
+								record.funding_txid = Some(funding_txo.txid.to_string());
+								record.funding_output_index = Some(funding_txo.vout);
+								record.channel_value_sats = channel.as_ref().map(|c| c.channel_value_sats);
+								record.is_outbound = channel.as_ref().map(|c| c.is_outbound);
+								record.pending_at = Some(utils::unix_timestamp());
+							});
+							close_unwanted_inbound_channel(
+								&event_node,
+								&config.inbound_channels,
+								&user_channel_id,
+								counterparty_node_id,
+							);
 						},
-						Event::ChannelReady { channel_id, counterparty_node_id, .. } => {
+						Event::ChannelReady { channel_id, user_channel_id, counterparty_node_id } => {
 							println!(
 								"CHANNEL_READY: {} from counterparty {:?}",
 								channel_id, counterparty_node_id
 								);
+							update_channel_record(&channel_history, &user_channel_id, |record| {
+								record.channel_id = channel_id.to_string();
+								if let Some(counterparty_node_id) = counterparty_node_id {
+									record.counterparty_node_id = Some(counterparty_node_id.to_string());
+								}
+								record.ready_at = Some(utils::unix_timestamp());
+							});
 						},
-						Event::PaymentReceived { payment_id, payment_hash, amount_msat } => {
+						Event::ChannelClosed { channel_id, user_channel_id, counterparty_node_id, reason } => {
+							println!(
+								"CHANNEL_CLOSED: {} from counterparty {:?}, reason {:?}",
+								channel_id, counterparty_node_id, reason
+								);
+							let (final_balance_sats, final_pending_htlc_balance_sats) =
+								closed_channel_balances(&event_node, channel_id);
+							update_channel_record(&channel_history, &user_channel_id, |record| {
+								record.channel_id = channel_id.to_string();
+								if let Some(counterparty_node_id) = counterparty_node_id {
+									record.counterparty_node_id = Some(counterparty_node_id.to_string());
+								}
+								record.closed_at = Some(utils::unix_timestamp());
+								record.closure_reason = reason.map(|reason| reason.to_string());
+								record.final_balance_sats = Some(final_balance_sats);
+								record.final_pending_htlc_balance_sats =
+									Some(final_pending_htlc_balance_sats);
+							});
+							record_closing_txid(
+								&runtime,
+								Arc::clone(&channel_history),
+								config.esplora_server_url.clone(),
+								user_channel_id,
+							);
+						},
+						Event::PaymentFailed { payment_id, payment_hash, reason } => {
+							println!(
+								"PAYMENT_FAILED: with id {:?}, hash {:?}, reason {:?}",
+								payment_id, payment_hash, reason
+								);
+							if let (Some(payment_id), Some(reason)) = (payment_id, reason) {
+								let reason = service::to_payment_failure_reason_proto(reason);
+								if let Err(e) = payment_failure_reasons.insert(
+									utils::payment_id_to_hex(&payment_id),
+									reason.as_str_name().to_string(),
+								) {
+									eprintln!("Failed to persist payment failure reason: {}", e);
+								}
+							}
+							if let Some(payment_id) = payment_id {
+								spending_policy.release_spend(&utils::payment_id_to_hex(&payment_id));
+							}
+							// Claimable payments not claimed in time are failed back.
+							if let Some(payment_hash) = payment_hash {
+								service::forget_claimable_payment(&claimable_payments, &payment_hash);
+							}
+						},
+						Event::PaymentClaimable {
+							payment_id,
+							payment_hash,
+							claimable_amount_msat,
+							claim_deadline,
+							..
+						} => {
+							println!(
+								"PAYMENT_CLAIMABLE: with id {:?}, hash {}, claimable_amount_msat {}, claim_deadline {:?}",
+								payment_id, payment_hash, claimable_amount_msat, claim_deadline
+								);
+							if let Err(e) = claimable_payments.insert(
+								payment_hash.to_string(),
+								ClaimablePayment { claimable_amount_msat, claim_deadline },
+							) {
+								eprintln!("Failed to persist claimable payment: {}", e);
+							}
+						},
+						Event::PaymentReceived { payment_id, payment_hash, amount_msat, .. } => {
 							println!(
 								"PAYMENT_RECEIVED: with id {:?}, hash {}, amount_msat {}",
 								payment_id, payment_hash, amount_msat
 								);
+							service::forget_claimable_payment(&claimable_payments, &payment_hash);
+						},
+						Event::PaymentForwarded {
+							prev_channel_id,
+							next_channel_id,
+							prev_node_id,
+							next_node_id,
+							total_fee_earned_msat,
+							outbound_amount_forwarded_msat,
+							..
+						} => {
+							println!(
+								"PAYMENT_FORWARDED: from channel {} to channel {}, fee earned {:?} msat",
+								prev_channel_id, next_channel_id, total_fee_earned_msat
+								);
+							let forward = ForwardedPayment {
+								prev_channel_id: prev_channel_id.to_string(),
+								next_channel_id: next_channel_id.to_string(),
+								prev_counterparty_node_id: prev_node_id.map(|id| id.to_string()),
+								next_counterparty_node_id: next_node_id.map(|id| id.to_string()),
+								total_fee_earned_msat,
+								outbound_amount_forwarded_msat,
+								timestamp: utils::unix_timestamp(),
+							};
+							if let Err(e) = forwarded_payments.append(forward) {
+								eprintln!("Failed to persist forwarded payment: {}", e);
+							}
 						},
 						_ => {},
 					}
-					event_node.event_handled();
+					if let Err(e) = event_node.event_handled() {
+						eprintln!("Failed to mark event as handled: {}", e);
+					}
 				},
 				res = rest_svc_listener.accept() => {
 					match res {
-						Ok((stream, _)) => {
+						Ok((stream, client_addr)) => {
 							let io_stream = TokioIo::new(stream);
-							let node_service = NodeService::new(Arc::clone(&node));
+							let node_service = NodeService::new(
+								Arc::clone(&node),
+								Arc::clone(&payment_failure_reasons),
+								Arc::clone(&issued_invoices),
+								Arc::clone(&claimable_payments),
+								Arc::clone(&payment_metadata),
+								Arc::clone(&forwarded_payments),
+								Arc::clone(&channel_history),
+								Arc::clone(&spending_policy),
+								Arc::clone(&audit_log),
+								Arc::clone(&idempotency_store),
+								Arc::clone(&fee_rate_estimates),
+								client_addr,
+							);
 							runtime.spawn(async move {
 								if let Err(err) = http1::Builder::new().serve_connection(io_stream, node_service).await {
 									eprintln!("Failed to serve connection: {}", err);
@@ -108,3 +342,101 @@ fn main() {
 	node.stop().unwrap();
 	println!("Shutdown complete..");
 }
+
+/// Applies the given update to the history of a channel, creating its record if needed.
+fn update_channel_record(
+	channel_history: &JsonLinesMap<ChannelRecord>, user_channel_id: &UserChannelId,
+	update: impl FnOnce(&mut ChannelRecord),
+) {
+	if let Err(e) = channel_history.update(user_channel_id.0.to_string(), update) {
+		eprintln!("Failed to persist history of channel {}: {}", user_channel_id.0, e);
+	}
+}
+
+/// Returns our balance claimable on-chain from the given closed channel, excluding HTLCs, and the
+/// balance of the HTLCs we may still be able to claim.
+fn closed_channel_balances(node: &Node, closed_channel_id: ChannelId) -> (u64, u64) {
+	let mut balance_sats = 0;
+	let mut pending_htlc_balance_sats = 0;
+	for balance in node.list_balances().lightning_balances {
+		match balance {
+			LightningBalance::ClaimableOnChannelClose { channel_id, amount_satoshis, .. }
+			| LightningBalance::ClaimableAwaitingConfirmations {
+				channel_id,
+				amount_satoshis,
+				..
+			}
+			| LightningBalance::CounterpartyRevokedOutputClaimable {
+				channel_id,
+				amount_satoshis,
+				..
+			} if channel_id == closed_channel_id => balance_sats += amount_satoshis,
+			LightningBalance::ContentiousClaimable { channel_id, amount_satoshis, .. }
+			| LightningBalance::MaybeTimeoutClaimableHTLC { channel_id, amount_satoshis, .. }
+			| LightningBalance::MaybePreimageClaimableHTLC {
+				channel_id, amount_satoshis, ..
+			} if channel_id == closed_channel_id => pending_htlc_balance_sats += amount_satoshis,
+			_ => {},
+		}
+	}
+	(balance_sats, pending_htlc_balance_sats)
+}
+
+/// Looks up the transaction spending the funding output of a closed channel in the background and
+/// records it as the channel's closing transaction.
+///
+/// The lookup goes through the Esplora server, the only chain source the server supports. If it
+/// gives up, it is retried on the next startup.
+fn record_closing_txid(
+	runtime: &Runtime, channel_history: Arc<JsonLinesMap<ChannelRecord>>,
+	esplora_server_url: String, user_channel_id: UserChannelId,
+) {
+	let funding_txo = channel_history.get(&user_channel_id.0.to_string()).and_then(|record| {
+		let txid = Txid::from_str(&record.funding_txid?).ok()?;
+		Some(OutPoint { txid, vout: record.funding_output_index? })
+	});
+	let funding_txo = match funding_txo {
+		Some(funding_txo) => funding_txo,
+		None => return,
+	};
+	runtime.spawn(async move {
+		if let Some(closing_txid) =
+			esplora::find_spending_txid(&esplora_server_url, funding_txo).await
+		{
+			update_channel_record(&channel_history, &user_channel_id, |record| {
+				record.closing_txid = Some(closing_txid.to_string());
+			});
+		}
+	});
+}
+
+/// Closes the given channel if it was opened by our counterparty and is unwanted according to our
+/// inbound channel policy.
+///
+/// The channel has already been accepted at this point, as inbound channels can't be rejected up
+/// front.
+fn close_unwanted_inbound_channel(
+	node: &Node, policy: &InboundChannelsConfig, user_channel_id: &UserChannelId,
+	counterparty_node_id: PublicKey,
+) {
+	let channel =
+		match node.list_channels().into_iter().find(|c| &c.user_channel_id == user_channel_id) {
+			Some(channel) => channel,
+			None => return,
+		};
+	if channel.is_outbound {
+		return;
+	}
+
+	let below_min_size = channel.channel_value_sats < policy.close_channels_below_sats;
+	let unannounced = policy.close_unannounced_channels && !channel.is_announced;
+	if below_min_size || unannounced {
+		println!(
+			"Closing inbound channel {} from counterparty {} as it is unwanted by our inbound channel policy",
+			channel.channel_id, counterparty_node_id
+		);
+		if let Err(e) = node.close_channel(user_channel_id, counterparty_node_id) {
+			eprintln!("Failed to close channel {}: {}", channel.channel_id, e);
+		}
+	}
+}
@@ -0,0 +1,80 @@
+use bip21::de::{DeserializationState, ParamKind};
+use bip21::{DeserializationError, DeserializeParams, Param};
+use ldk_node::bitcoin::address::NetworkUnchecked;
+use ldk_node::NodeError;
+
+/// A BIP21 URI, which may offer a BOLT11 invoice and a BOLT12 offer besides the on-chain address.
+pub(crate) type PaymentUri<'a> = bip21::Uri<'a, NetworkUnchecked, LightningParams>;
+
+pub(crate) fn parse(uri: &str) -> Result<PaymentUri<'_>, NodeError> {
+	uri.parse().map_err(|_| NodeError::InvalidUri)
+}
+
+/// The Lightning payment methods offered by a BIP21 URI, left for the respective send handler to
+/// parse.
+#[derive(Default)]
+pub(crate) struct LightningParams {
+	/// The `lightning` parameter.
+	pub(crate) bolt11_invoice: Option<String>,
+	/// The `lno` parameter.
+	pub(crate) bolt12_offer: Option<String>,
+}
+
+impl<'a> DeserializeParams<'a> for LightningParams {
+	type DeserializationState = LightningParams;
+}
+
+impl DeserializationError for LightningParams {
+	type Error = NodeError;
+}
+
+impl<'a> DeserializationState<'a> for LightningParams {
+	type Value = LightningParams;
+
+	fn is_param_known(&self, key: &str) -> bool {
+		key == "lightning" || key == "lno"
+	}
+
+	fn deserialize_temp(&mut self, key: &str, value: Param<'_>) -> Result<ParamKind, NodeError> {
+		let param = match key {
+			"lightning" => &mut self.bolt11_invoice,
+			"lno" => &mut self.bolt12_offer,
+			_ => return Ok(ParamKind::Unknown),
+		};
+		*param = Some(String::try_from(value).map_err(|_| NodeError::UriParameterParsingFailed)?);
+		Ok(ParamKind::Known)
+	}
+
+	fn finalize(self) -> Result<LightningParams, NodeError> {
+		Ok(self)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_uri_with_invoice_and_offer() {
+		let uri = parse(
+			"BITCOIN:BCRT1QM0NW9S05QDPGC6F52FPKA9U6Q6VWTT5WVS30R2?amount=0.001&message=asdf&lightning=LNBCRT1INVOICE&lno=LNO1OFFER",
+		)
+		.unwrap();
+		assert_eq!(uri.amount.map(|amount| amount.to_sat()), Some(100_000));
+		assert_eq!(uri.extras.bolt11_invoice.as_deref(), Some("LNBCRT1INVOICE"));
+		assert_eq!(uri.extras.bolt12_offer.as_deref(), Some("LNO1OFFER"));
+	}
+
+	#[test]
+	fn test_parse_uri_without_lightning_params() {
+		let uri = parse("bitcoin:bcrt1qm0nw9s05qdpgc6f52fpka9u6q6vwtt5wvs30r2").unwrap();
+		assert_eq!(uri.amount, None);
+		assert!(uri.extras.bolt11_invoice.is_none());
+		assert!(uri.extras.bolt12_offer.is_none());
+	}
+
+	#[test]
+	fn test_parse_invalid_uri() {
+		assert!(parse("lightning:LNBCRT1INVOICE").is_err());
+	}
+}
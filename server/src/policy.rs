@@ -0,0 +1,553 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ldk_node::NodeError;
+use prost::Message;
+
+use crate::config::SpendingPolicyConfig;
+use crate::service::HandlerError;
+use crate::store::{JsonFileStore, OutgoingPaymentKind, ParkedPayment, SpendRecord};
+use crate::utils;
+
+const HOUR_SECS: u64 = 60 * 60;
+const DAY_SECS: u64 = 24 * HOUR_SECS;
+
+/// An outgoing payment to be checked against the spending policy.
+pub(crate) struct OutgoingPayment {
+	pub(crate) kind: OutgoingPaymentKind,
+	pub(crate) amount_msat: u64,
+	/// The recipient's node id, or the address for on-chain payments.
+	pub(crate) destination: String,
+}
+
+/// The response to a sent payment.
+pub(crate) trait SentPayment {
+	/// The id of the payment, under which its spend is recorded until it fails.
+	fn payment_id(&self) -> String;
+}
+
+pub(crate) enum Authorized<R> {
+	Sent(R),
+	/// The payment was parked until it is approved, under the given approval id.
+	Parked(String),
+}
+
+/// Enforces the configured spending limits, destination lists and approval requirements on
+/// outgoing payments.
+pub(crate) struct SpendingPolicy {
+	config: SpendingPolicyConfig,
+	spends: JsonFileStore<SpendRecord>,
+	parked_payments: JsonFileStore<ParkedPayment>,
+	/// Held from checking a payment against the limits until its amount is reserved, so that
+	/// concurrent payments can't jointly exceed them.
+	lock: Mutex<()>,
+}
+
+impl SpendingPolicy {
+	pub(crate) fn new(
+		config: SpendingPolicyConfig, spends: JsonFileStore<SpendRecord>,
+		parked_payments: JsonFileStore<ParkedPayment>,
+	) -> Self {
+		Self { config, spends, parked_payments, lock: Mutex::new(()) }
+	}
+
+	/// Sends the payment by passing the request to `send` if the policy allows it, or parks the
+	/// request if the payment needs to be approved first.
+	pub(crate) fn authorize<T: Message, R: SentPayment, E: Into<HandlerError>>(
+		&self, payment: OutgoingPayment, request: T, send: impl FnOnce(T) -> Result<R, E>,
+	) -> Result<Authorized<R>, HandlerError> {
+		let guard = self.lock.lock().unwrap();
+		self.check(&payment)?;
+
+		let requires_approval = match self.config.require_approval_above_sats {
+			Some(threshold_sats) => payment.amount_msat > threshold_sats.saturating_mul(1000),
+			None => false,
+		};
+		if requires_approval {
+			let approval_id = unique_id();
+			let parked_payment = ParkedPayment {
+				kind: payment.kind,
+				amount_msat: payment.amount_msat,
+				destination: payment.destination,
+				request: utils::to_hex(&request.encode_to_vec()),
+				created_at: utils::unix_timestamp(),
+			};
+			self.parked_payments
+				.insert(approval_id.clone(), parked_payment)
+				.map_err(|_| NodeError::PersistenceFailed)?;
+			return Ok(Authorized::Parked(approval_id));
+		}
+		let reservation = self.reserve(&payment)?;
+		drop(guard);
+
+		let response = match send(request).map_err(Into::into) {
+			Ok(response) => response,
+			Err(e) => {
+				self.release_reservation(reservation);
+				return Err(e);
+			},
+		};
+		self.record_spend(reservation, response.payment_id())?;
+		Ok(Authorized::Sent(response))
+	}
+
+	/// Sends a parked payment via `send` once it is approved. The payment still has to be within
+	/// the limits at this point.
+	pub(crate) fn approve<R: SentPayment, E: Into<HandlerError>>(
+		&self, approval_id: &str, approval_token: &str,
+		send: impl FnOnce(ParkedPayment) -> Result<R, E>,
+	) -> Result<R, HandlerError> {
+		self.check_approval_token(approval_token)?;
+		let guard = self.lock.lock().unwrap();
+		let parked_payment = self.parked_payment(approval_id)?;
+		let payment = OutgoingPayment {
+			kind: parked_payment.kind,
+			amount_msat: parked_payment.amount_msat,
+			destination: parked_payment.destination.clone(),
+		};
+		self.check(&payment)?;
+
+		// Forget the payment before sending it, so that it can't be sent twice, and park it again if
+		// sending fails.
+		self.parked_payments.remove(approval_id).map_err(|_| NodeError::PersistenceFailed)?;
+		let reservation = match self.reserve(&payment) {
+			Ok(reservation) => reservation,
+			Err(e) => {
+				self.park_again(approval_id, parked_payment);
+				return Err(e);
+			},
+		};
+		drop(guard);
+
+		let response = match send(parked_payment.clone()).map_err(Into::into) {
+			Ok(response) => response,
+			Err(e) => {
+				self.release_reservation(reservation);
+				self.park_again(approval_id, parked_payment);
+				return Err(e);
+			},
+		};
+		self.record_spend(reservation, response.payment_id())?;
+		Ok(response)
+	}
+
+	fn park_again(&self, approval_id: &str, parked_payment: ParkedPayment) {
+		if let Err(e) = self.parked_payments.insert(approval_id.to_string(), parked_payment) {
+			eprintln!("Failed to park payment {} again: {}", approval_id, e);
+		}
+	}
+
+	pub(crate) fn reject(
+		&self, approval_id: &str, approval_token: &str,
+	) -> Result<(), HandlerError> {
+		self.check_approval_token(approval_token)?;
+		let _guard = self.lock.lock().unwrap();
+		self.parked_payment(approval_id)?;
+		self.parked_payments.remove(approval_id).map_err(|_| NodeError::PersistenceFailed)?;
+		Ok(())
+	}
+
+	/// Stops counting the spend of a failed payment towards the limits.
+	pub(crate) fn release_spend(&self, payment_id: &str) {
+		let _guard = self.lock.lock().unwrap();
+		if let Err(e) = self.spends.remove(payment_id) {
+			eprintln!("Failed to release spend of payment {}: {}", payment_id, e);
+		}
+	}
+
+	pub(crate) fn list_parked_payments(&self) -> Vec<(String, ParkedPayment)> {
+		self.parked_payments.list_entries()
+	}
+
+	fn parked_payment(&self, approval_id: &str) -> Result<ParkedPayment, HandlerError> {
+		self.parked_payments.get(approval_id).ok_or_else(|| {
+			HandlerError::InvalidRequest(format!("Unknown approval id: {}", approval_id))
+		})
+	}
+
+	fn check_approval_token(&self, approval_token: &str) -> Result<(), HandlerError> {
+		match &self.config.approval_token {
+			Some(expected_token) if constant_time_eq(expected_token, approval_token) => Ok(()),
+			_ => Err(HandlerError::PolicyViolation("Invalid approval token".to_string())),
+		}
+	}
+
+	fn check(&self, payment: &OutgoingPayment) -> Result<(), HandlerError> {
+		let is_destination =
+			|destination: &String| destination.eq_ignore_ascii_case(&payment.destination);
+		if self.config.denied_destinations.iter().any(is_destination) {
+			return Err(HandlerError::PolicyViolation(format!(
+				"Payments to {} are denied",
+				payment.destination
+			)));
+		}
+		if !self.config.allowed_destinations.is_empty()
+			&& !self.config.allowed_destinations.iter().any(is_destination)
+		{
+			return Err(HandlerError::PolicyViolation(format!(
+				"Payments to {} are not allowed",
+				payment.destination
+			)));
+		}
+
+		if let Some(max_payment_sats) = self.config.max_payment_sats {
+			if payment.amount_msat > max_payment_sats.saturating_mul(1000) {
+				return Err(HandlerError::PolicyViolation(format!(
+					"Payment of {} msat exceeds the per-payment limit of {} sats",
+					payment.amount_msat, max_payment_sats
+				)));
+			}
+		}
+
+		let now = utils::unix_timestamp();
+		let limits = [
+			("hourly", self.config.max_hourly_sats, HOUR_SECS),
+			("daily", self.config.max_daily_sats, DAY_SECS),
+		];
+		for (name, limit_sats, window_secs) in limits {
+			let limit_sats = match limit_sats {
+				Some(limit_sats) => limit_sats,
+				None => continue,
+			};
+			let spent_msat: u64 = self
+				.spends
+				.list()
+				.iter()
+				.filter(|spend| spend.timestamp + window_secs > now)
+				.fold(0u64, |spent_msat, spend| spent_msat.saturating_add(spend.amount_msat));
+			if spent_msat.saturating_add(payment.amount_msat) > limit_sats.saturating_mul(1000) {
+				return Err(HandlerError::PolicyViolation(format!(
+					"Payment of {} msat exceeds the {} limit of {} sats, of which {} msat are already spent",
+					payment.amount_msat, name, limit_sats, spent_msat
+				)));
+			}
+		}
+		Ok(())
+	}
+
+	/// Counts the payment towards the limits before it is sent, returning the key under which its
+	/// spend is recorded, if any limits are configured. Must be called with `lock` held.
+	fn reserve(&self, payment: &OutgoingPayment) -> Result<Option<String>, HandlerError> {
+		if self.config.max_hourly_sats.is_none() && self.config.max_daily_sats.is_none() {
+			return Ok(None);
+		}
+		let now = utils::unix_timestamp();
+		let reservation = format!("reserved-{}", unique_id());
+		let spend = SpendRecord {
+			timestamp: now,
+			amount_msat: payment.amount_msat,
+			destination: payment.destination.clone(),
+		};
+		// Spends outside of the longest window no longer count towards any limit.
+		self.spends
+			.retain(|_, spend| spend.timestamp + DAY_SECS > now)
+			.and_then(|()| self.spends.insert(reservation.clone(), spend))
+			.map_err(|e| {
+				eprintln!("Failed to persist spend to {}: {}", payment.destination, e);
+				NodeError::PersistenceFailed
+			})?;
+		Ok(Some(reservation))
+	}
+
+	/// Records the reserved spend of a sent payment under its payment id, so that it can be released
+	/// if the payment fails.
+	fn record_spend(
+		&self, reservation: Option<String>, payment_id: String,
+	) -> Result<(), HandlerError> {
+		let reservation = match reservation {
+			Some(reservation) => reservation,
+			None => return Ok(()),
+		};
+		let _guard = self.lock.lock().unwrap();
+		let spend = match self.spends.get(&reservation) {
+			Some(spend) => spend,
+			None => return Ok(()),
+		};
+		self.spends.insert(payment_id.clone(), spend).map_err(|e| {
+			eprintln!("Failed to persist spend of payment {}: {}", payment_id, e);
+			NodeError::PersistenceFailed
+		})?;
+		// Failing to remove the reservation only counts the spend twice until it expires.
+		if let Err(e) = self.spends.remove(&reservation) {
+			eprintln!("Failed to remove spend reservation {}: {}", reservation, e);
+		}
+		Ok(())
+	}
+
+	fn release_reservation(&self, reservation: Option<String>) {
+		if let Some(reservation) = reservation {
+			self.release_spend(&reservation);
+		}
+	}
+}
+
+/// Compares the tokens without short-circuiting on the first differing byte, so that the time taken
+/// doesn't reveal how much of a guessed token is correct.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+	let (a, b) = (a.as_bytes(), b.as_bytes());
+	a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn unique_id() -> String {
+	static COUNTER: AtomicU64 = AtomicU64::new(0);
+	let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+	format!("{:x}{:04x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed) & 0xffff)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use ldk_node::bitcoin::hashes::hex::FromHex;
+	use protos::{SpontaneousSendRequest, SpontaneousSendResponse};
+
+	use super::*;
+
+	const NODE_ID: &str = "027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190";
+	const OTHER_NODE_ID: &str =
+		"03a6ce61fcaacd38d31d4e3ce2d506602818e3856b4b44faff1dde9642ba705976";
+
+	fn spending_policy(name: &str, config: SpendingPolicyConfig) -> SpendingPolicy {
+		let dir =
+			std::env::temp_dir().join(format!("spending_policy_{}_{}", name, std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+		fs::create_dir_all(&dir).unwrap();
+		SpendingPolicy::new(
+			config,
+			JsonFileStore::open(dir.join("spends.json")).unwrap(),
+			JsonFileStore::open(dir.join("parked_payments.json")).unwrap(),
+		)
+	}
+
+	fn payment(amount_msat: u64, destination: &str) -> OutgoingPayment {
+		OutgoingPayment {
+			kind: OutgoingPaymentKind::Spontaneous,
+			amount_msat,
+			destination: destination.to_string(),
+		}
+	}
+
+	fn request(amount_msat: u64) -> SpontaneousSendRequest {
+		SpontaneousSendRequest { node_id: NODE_ID.to_string(), amount_msat, ..Default::default() }
+	}
+
+	fn sent(payment_id: u8) -> SpontaneousSendResponse {
+		SpontaneousSendResponse {
+			payment_id: Some(protos::PaymentId { data: vec![payment_id; 32] }),
+			approval_id: None,
+		}
+	}
+
+	fn send(
+		policy: &SpendingPolicy, amount_msat: u64, payment_id: u8,
+	) -> Result<Authorized<SpontaneousSendResponse>, HandlerError> {
+		policy.authorize(payment(amount_msat, NODE_ID), request(amount_msat), |_| {
+			Ok::<_, HandlerError>(sent(payment_id))
+		})
+	}
+
+	fn is_policy_violation<T>(result: Result<T, HandlerError>) -> bool {
+		matches!(result, Err(HandlerError::PolicyViolation(_)))
+	}
+
+	#[test]
+	fn test_check_destinations() {
+		let denied = spending_policy(
+			"check_denied",
+			SpendingPolicyConfig {
+				denied_destinations: vec![NODE_ID.to_uppercase()],
+				..Default::default()
+			},
+		);
+		assert!(is_policy_violation(denied.check(&payment(1000, NODE_ID))));
+		assert!(denied.check(&payment(1000, OTHER_NODE_ID)).is_ok());
+
+		let allowed = spending_policy(
+			"check_allowed",
+			SpendingPolicyConfig {
+				allowed_destinations: vec![NODE_ID.to_string()],
+				..Default::default()
+			},
+		);
+		assert!(allowed.check(&payment(1000, NODE_ID)).is_ok());
+		assert!(is_policy_violation(allowed.check(&payment(1000, OTHER_NODE_ID))));
+	}
+
+	#[test]
+	fn test_check_max_payment() {
+		let policy = spending_policy(
+			"check_max_payment",
+			SpendingPolicyConfig { max_payment_sats: Some(10), ..Default::default() },
+		);
+		assert!(policy.check(&payment(10_000, NODE_ID)).is_ok());
+		assert!(is_policy_violation(policy.check(&payment(10_001, NODE_ID))));
+
+		// Limits too large to be expressed in msat don't overflow.
+		let policy = spending_policy(
+			"check_max_payment_overflow",
+			SpendingPolicyConfig { max_payment_sats: Some(u64::MAX), ..Default::default() },
+		);
+		assert!(policy.check(&payment(u64::MAX, NODE_ID)).is_ok());
+	}
+
+	#[test]
+	fn test_authorize_enforces_daily_limit() {
+		let policy = spending_policy(
+			"authorize_daily_limit",
+			SpendingPolicyConfig { max_daily_sats: Some(10), ..Default::default() },
+		);
+		assert!(matches!(send(&policy, 6_000, 1), Ok(Authorized::Sent(_))));
+		assert!(is_policy_violation(send(&policy, 6_000, 2)));
+		assert!(matches!(send(&policy, 4_000, 3), Ok(Authorized::Sent(_))));
+		assert!(is_policy_violation(send(&policy, 1, 4)));
+	}
+
+	#[test]
+	fn test_authorize_does_not_record_unsent_payments() {
+		let policy = spending_policy(
+			"authorize_unsent",
+			SpendingPolicyConfig { max_daily_sats: Some(10), ..Default::default() },
+		);
+		let result = policy.authorize(payment(6_000, NODE_ID), request(6_000), |_| {
+			Err::<SpontaneousSendResponse, _>(NodeError::PaymentSendingFailed)
+		});
+		assert!(matches!(result, Err(HandlerError::Node(NodeError::PaymentSendingFailed))));
+		assert!(matches!(send(&policy, 6_000, 1), Ok(Authorized::Sent(_))));
+	}
+
+	#[test]
+	fn test_authorize_reserves_amount_while_sending() {
+		let policy = spending_policy(
+			"authorize_reserves",
+			SpendingPolicyConfig { max_daily_sats: Some(10), ..Default::default() },
+		);
+		let result = policy.authorize(payment(6_000, NODE_ID), request(6_000), |_| {
+			// A payment authorized while the first one is being sent sees its amount as spent.
+			assert!(is_policy_violation(send(&policy, 6_000, 2)));
+			assert!(matches!(send(&policy, 4_000, 3), Ok(Authorized::Sent(_))));
+			Ok::<_, HandlerError>(sent(1))
+		});
+		assert!(matches!(result, Ok(Authorized::Sent(_))));
+		assert!(is_policy_violation(send(&policy, 1, 4)));
+
+		// The spend is recorded under the payment id, so that it can be released.
+		policy.release_spend(&sent(1).payment_id());
+		assert!(matches!(send(&policy, 6_000, 5), Ok(Authorized::Sent(_))));
+	}
+
+	#[test]
+	fn test_release_spend_of_failed_payment() {
+		let policy = spending_policy(
+			"release_spend",
+			SpendingPolicyConfig { max_hourly_sats: Some(10), ..Default::default() },
+		);
+		assert!(matches!(send(&policy, 10_000, 1), Ok(Authorized::Sent(_))));
+		assert!(is_policy_violation(send(&policy, 1_000, 2)));
+
+		policy.release_spend(&sent(1).payment_id());
+		assert!(matches!(send(&policy, 10_000, 2), Ok(Authorized::Sent(_))));
+	}
+
+	#[test]
+	fn test_authorize_parks_payments_above_threshold() {
+		let policy = spending_policy(
+			"authorize_parks",
+			SpendingPolicyConfig { require_approval_above_sats: Some(5), ..Default::default() },
+		);
+		assert!(matches!(send(&policy, 5_000, 1), Ok(Authorized::Sent(_))));
+
+		let result = policy.authorize(
+			payment(5_001, NODE_ID),
+			request(5_001),
+			|_| -> Result<SpontaneousSendResponse, HandlerError> {
+				panic!("Parked payment was sent")
+			},
+		);
+		let approval_id = match result {
+			Ok(Authorized::Parked(approval_id)) => approval_id,
+			_ => panic!("Payment wasn't parked"),
+		};
+		let parked_payments = policy.list_parked_payments();
+		assert_eq!(parked_payments.len(), 1);
+		assert_eq!(parked_payments[0].0, approval_id);
+		assert_eq!(parked_payments[0].1.amount_msat, 5_001);
+		assert_eq!(parked_payments[0].1.destination, NODE_ID);
+	}
+
+	#[test]
+	fn test_approve() {
+		let policy = spending_policy(
+			"approve",
+			SpendingPolicyConfig {
+				max_daily_sats: Some(100),
+				require_approval_above_sats: Some(5),
+				approval_token: Some("secret".to_string()),
+				..Default::default()
+			},
+		);
+		let approval_id = match send(&policy, 60_000, 1) {
+			Ok(Authorized::Parked(approval_id)) => approval_id,
+			_ => panic!("Payment wasn't parked"),
+		};
+
+		let approve = |token: &str| {
+			policy.approve(&approval_id, token, |parked_payment| {
+				let request = SpontaneousSendRequest::decode(
+					&*Vec::<u8>::from_hex(&parked_payment.request).unwrap(),
+				)
+				.unwrap();
+				assert_eq!(request, self::request(60_000));
+				Ok::<_, HandlerError>(sent(1))
+			})
+		};
+		assert!(is_policy_violation(approve("wrong")));
+		assert!(is_policy_violation(approve("secre")));
+		assert_eq!(approve("secret").unwrap(), sent(1));
+		assert!(policy.list_parked_payments().is_empty());
+		assert!(matches!(approve("secret"), Err(HandlerError::InvalidRequest(_))));
+
+		// The approved payment counts towards the limits.
+		assert!(is_policy_violation(send(&policy, 50_000, 2)));
+	}
+
+	#[test]
+	fn test_approve_parks_payment_again_if_sending_fails() {
+		let policy = spending_policy(
+			"approve_fails",
+			SpendingPolicyConfig {
+				require_approval_above_sats: Some(5),
+				approval_token: Some("secret".to_string()),
+				..Default::default()
+			},
+		);
+		let approval_id = match send(&policy, 6_000, 1) {
+			Ok(Authorized::Parked(approval_id)) => approval_id,
+			_ => panic!("Payment wasn't parked"),
+		};
+
+		let result = policy.approve(&approval_id, "secret", |_| {
+			Err::<SpontaneousSendResponse, _>(NodeError::PaymentSendingFailed)
+		});
+		assert!(matches!(result, Err(HandlerError::Node(NodeError::PaymentSendingFailed))));
+		assert_eq!(policy.list_parked_payments().len(), 1);
+
+		let result = policy.approve(&approval_id, "secret", |_| Ok::<_, HandlerError>(sent(1)));
+		assert!(result.is_ok());
+		assert!(policy.list_parked_payments().is_empty());
+	}
+
+	#[test]
+	fn test_approval_requires_configured_token() {
+		let policy = spending_policy(
+			"approve_without_token",
+			SpendingPolicyConfig { require_approval_above_sats: Some(5), ..Default::default() },
+		);
+		let approval_id = match send(&policy, 6_000, 1) {
+			Ok(Authorized::Parked(approval_id)) => approval_id,
+			_ => panic!("Payment wasn't parked"),
+		};
+		assert!(is_policy_violation(policy.reject(&approval_id, "")));
+		assert_eq!(policy.list_parked_payments().len(), 1);
+	}
+}
@@ -1,9 +1,22 @@
+use ldk_node::bitcoin::hashes::hex::FromHex;
+use ldk_node::bitcoin::hashes::Hash;
 use ldk_node::bitcoin::secp256k1::PublicKey;
-use ldk_node::bitcoin::Address;
+use ldk_node::bitcoin::{Address, FeeRate, Txid};
+use ldk_node::config::{ChannelConfig, MaxDustHTLCExposure};
 use ldk_node::lightning::chain::BestBlock;
+use ldk_node::lightning::events::PaymentFailureReason;
+use ldk_node::lightning::ln::channelmanager::PaymentId;
 use ldk_node::lightning::ln::msgs::SocketAddress;
-use ldk_node::lightning_invoice::Bolt11Invoice;
-use ldk_node::payment::{PaymentDetails, PaymentDirection, PaymentKind, PaymentStatus};
+use ldk_node::lightning::offers::offer::{Amount, Offer};
+use ldk_node::lightning::routing::gossip::{ChannelInfo, ChannelUpdateInfo, NodeId, NodeInfo};
+use ldk_node::lightning_invoice::{
+	Bolt11Invoice, Bolt11InvoiceDescription, Bolt11InvoiceDescriptionRef, Description,
+};
+use ldk_node::lightning_types::payment::{PaymentHash, PaymentPreimage};
+use ldk_node::payment::{
+	ConfirmationStatus, PaymentDetails, PaymentDirection, PaymentKind, PaymentStatus,
+	SendingParameters,
+};
 use ldk_node::LightningBalance::{
 	ClaimableAwaitingConfirmations, ClaimableOnChannelClose, ContentiousClaimable,
 	CounterpartyRevokedOutputClaimable, MaybePreimageClaimableHTLC, MaybeTimeoutClaimableHTLC,
@@ -11,9 +24,22 @@ use ldk_node::LightningBalance::{
 use ldk_node::PendingSweepBalance::{
 	AwaitingThresholdConfirmations, BroadcastAwaitingConfirmation, PendingBroadcast,
 };
-use ldk_node::{Node, UserChannelId};
+use ldk_node::{ChannelDetails, Node, UserChannelId};
 use prost::Message;
 
+use crate::audit::{AuditLog, AuditedRequest};
+use crate::esplora::FeeRateEstimates;
+use crate::export::{self, ExportCategory, ExportRecord};
+use crate::idempotency::{IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
+use crate::payment_uri;
+use crate::policy::{Authorized, OutgoingPayment, SentPayment, SpendingPolicy};
+use crate::store::{
+	ChannelRecord, ClaimablePayment, ForwardedPayment, JsonFileStore, JsonLinesLog, JsonLinesMap,
+	OutgoingPaymentKind, PaymentMetadata,
+};
+use crate::utils::{self, payment_id_to_hex};
+
+use core::cmp::Reverse;
 use core::future::Future;
 use core::pin::Pin;
 use core::str::FromStr;
@@ -22,42 +48,178 @@ use hyper::body::{Bytes, Incoming};
 use hyper::service::Service;
 use hyper::{Request, Response, StatusCode};
 
+use std::collections::HashMap;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
 use protos::{
-	lightning_balance, pending_sweep_balance, Bolt11ReceiveRequest, Bolt11ReceiveResponse,
-	Bolt11SendRequest, Bolt11SendResponse, Channel, CloseChannelRequest, CloseChannelResponse,
-	ForceCloseChannelRequest, ForceCloseChannelResponse, GetBalancesRequest, GetBalancesResponse,
+	approve_payment_response, bolt11_decode_response, channel_config, channel_identifier,
+	lightning_balance, onchain_send_request, pending_sweep_balance, unified_send_response,
+	ApprovePaymentRequest, ApprovePaymentResponse, AuditLogEntry, Bolt11CancelRequest,
+	Bolt11CancelResponse, Bolt11ClaimRequest, Bolt11ClaimResponse, Bolt11DecodeRequest,
+	Bolt11DecodeResponse, Bolt11FailRequest, Bolt11FailResponse, Bolt11ProbeRequest,
+	Bolt11ProbeResponse, Bolt11ReceiveForHashRequest, Bolt11ReceiveForHashResponse,
+	Bolt11ReceiveRequest, Bolt11ReceiveResponse, Bolt11SendRequest, Bolt11SendResponse,
+	Bolt12SendRequest, Bolt12SendResponse, Channel, ChannelCloseFailure, CloseChannelRequest,
+	CloseChannelResponse, ClosedChannel, EsploraFeeRateEstimate, ExportFormat, ExportRequest,
+	ExportResponse, ForceCloseChannelRequest, ForceCloseChannelResponse, Forward, ForwardingIncome,
+	GetBalancesRequest, GetBalancesResponse, GetFeeEstimatesRequest, GetFeeEstimatesResponse,
+	GetGraphChannelRequest, GetGraphChannelResponse, GetGraphNodeRequest, GetGraphNodeResponse,
 	GetNodeIdRequest, GetNodeIdResponse, GetNodeStatusRequest, GetNodeStatusResponse,
-	GetPaymentDetailsRequest, ListChannelsRequest, ListChannelsResponse, OnchainReceiveRequest,
-	OnchainReceiveResponse, OnchainSendRequest, OnchainSendResponse, OpenChannelRequest,
+	GetPaymentDetailsRequest, GetPaymentsByHashRequest, GetPaymentsByHashResponse, GraphChannel,
+	GraphChannelUpdate, GraphNode, GraphNodeAnnouncement, InvoiceStatus, ListAuditLogRequest,
+	ListAuditLogResponse, ListChannelsRequest, ListChannelsResponse, ListClaimablePaymentsRequest,
+	ListClaimablePaymentsResponse, ListClosedChannelsRequest, ListClosedChannelsResponse,
+	ListForwardsRequest, ListForwardsResponse, ListGraphChannelsRequest, ListGraphChannelsResponse,
+	ListGraphNodesRequest, ListGraphNodesResponse, ListInvoicesRequest, ListInvoicesResponse,
+	ListOnchainTransactionsRequest, ListOnchainTransactionsResponse, ListPendingApprovalsRequest,
+	ListPendingApprovalsResponse, OnchainReceiveRequest, OnchainReceiveResponse,
+	OnchainSendRequest, OnchainSendResponse, OnchainTransaction, OpenChannelRequest,
 	OpenChannelResponse, Outpoint, PaymentsHistoryRequest, PaymentsHistoryResponse,
+	PendingApproval, RejectPaymentRequest, RejectPaymentResponse, SignMessageRequest,
+	SignMessageResponse, SpontaneousProbeRequest, SpontaneousProbeResponse, SpontaneousSendRequest,
+	SpontaneousSendResponse, UnifiedReceiveRequest, UnifiedReceiveResponse, UnifiedSendRequest,
+	UnifiedSendResponse, UpdateChannelConfigRequest, UpdateChannelConfigResponse,
+	VerifyAuditLogRequest, VerifyAuditLogResponse, VerifySignatureRequest, VerifySignatureResponse,
 };
 
 const GET_NODE_ID_PATH: &str = "/getNodeId";
 const GET_NODE_STATUS_PATH: &str = "/getNodeStatus";
+const SIGN_MESSAGE_PATH: &str = "/sign-message";
+const VERIFY_SIGNATURE_PATH: &str = "/verify-signature";
 const ONCHAIN_RECEIVE_PATH: &str = "/onchain/receive";
 const ONCHAIN_SEND_PATH: &str = "/onchain/send";
+const ONCHAIN_TRANSACTIONS_PATH: &str = "/onchain/transactions";
 const BOLT11_RECEIVE_PATH: &str = "/bolt11/receive";
 const BOLT11_SEND_PATH: &str = "/bolt11/send";
+const BOLT11_DECODE_PATH: &str = "/bolt11/decode";
+const BOLT11_LIST_INVOICES_PATH: &str = "/bolt11/invoices";
+const BOLT11_CANCEL_PATH: &str = "/bolt11/cancel";
+const BOLT11_RECEIVE_FOR_HASH_PATH: &str = "/bolt11/receive-for-hash";
+const BOLT11_CLAIMABLE_PATH: &str = "/bolt11/claimable";
+const BOLT11_CLAIM_PATH: &str = "/bolt11/claim";
+const BOLT11_FAIL_PATH: &str = "/bolt11/fail";
+const BOLT11_PROBE_PATH: &str = "/bolt11/probe";
+const BOLT12_SEND_PATH: &str = "/bolt12/send";
+const SPONTANEOUS_SEND_PATH: &str = "/spontaneous/send";
+const UNIFIED_RECEIVE_PATH: &str = "/unified/receive";
+const UNIFIED_SEND_PATH: &str = "/unified/send";
+const SPONTANEOUS_PROBE_PATH: &str = "/spontaneous/probe";
 const GET_NODE_BALANCES_PATH: &str = "/getNodeBalances";
+const GET_FEE_ESTIMATES_PATH: &str = "/getFeeEstimates";
 const PAYMENTS_HISTORY_PATH: &str = "/listPaymentsHistory";
 const GET_PAYMENT_DETAILS_PATH: &str = "/getPaymentDetails";
+const GET_PAYMENTS_BY_HASH_PATH: &str = "/payment/by-hash";
+const EXPORT_PATH: &str = "/export";
+const LIST_FORWARDS_PATH: &str = "/forwards/list";
+const LIST_PENDING_APPROVALS_PATH: &str = "/policy/pending";
+const APPROVE_PAYMENT_PATH: &str = "/policy/approve";
+const REJECT_PAYMENT_PATH: &str = "/policy/reject";
+const LIST_AUDIT_LOG_PATH: &str = "/audit/list";
+const VERIFY_AUDIT_LOG_PATH: &str = "/audit/verify";
 const LIST_CHANNELS_PATH: &str = "/channel/list";
+const LIST_CLOSED_CHANNELS_PATH: &str = "/channel/closed";
 const OPEN_CHANNEL_PATH: &str = "/channel/open";
 const CLOSE_CHANNEL_PATH: &str = "/channel/close";
 const FORCE_CLOSE_CHANNEL_PATH: &str = "/channel/force-close";
+const UPDATE_CHANNEL_CONFIG_PATH: &str = "/channel/update-config";
+const LIST_GRAPH_CHANNELS_PATH: &str = "/graph/channels";
+const GET_GRAPH_CHANNEL_PATH: &str = "/graph/channel";
+const LIST_GRAPH_NODES_PATH: &str = "/graph/nodes";
+const GET_GRAPH_NODE_PATH: &str = "/graph/node";
+
+const DEFAULT_GRAPH_PAGE_LIMIT: u32 = 100;
+const DEFAULT_FORWARDS_PAGE_LIMIT: u32 = 100;
+const DEFAULT_AUDIT_LOG_PAGE_LIMIT: u32 = 100;
 
 type Req = Request<Incoming>;
 
 #[derive(Clone)]
 pub struct NodeService {
 	node: Arc<Node>,
+	payment_failure_reasons: Arc<JsonFileStore<String>>,
+	issued_invoices: Arc<JsonFileStore<String>>,
+	claimable_payments: Arc<JsonFileStore<ClaimablePayment>>,
+	payment_metadata: Arc<JsonFileStore<PaymentMetadata>>,
+	forwarded_payments: Arc<JsonLinesLog<ForwardedPayment>>,
+	channel_history: Arc<JsonLinesMap<ChannelRecord>>,
+	spending_policy: Arc<SpendingPolicy>,
+	audit_log: Arc<AuditLog>,
+	idempotency_store: Arc<IdempotencyStore>,
+	fee_rate_estimates: Arc<FeeRateEstimates>,
+	client_addr: SocketAddr,
 }
 
 impl NodeService {
-	pub(crate) fn new(node: Arc<Node>) -> Self {
-		Self { node }
+	#[allow(clippy::too_many_arguments)]
+	pub(crate) fn new(
+		node: Arc<Node>, payment_failure_reasons: Arc<JsonFileStore<String>>,
+		issued_invoices: Arc<JsonFileStore<String>>,
+		claimable_payments: Arc<JsonFileStore<ClaimablePayment>>,
+		payment_metadata: Arc<JsonFileStore<PaymentMetadata>>,
+		forwarded_payments: Arc<JsonLinesLog<ForwardedPayment>>,
+		channel_history: Arc<JsonLinesMap<ChannelRecord>>, spending_policy: Arc<SpendingPolicy>,
+		audit_log: Arc<AuditLog>, idempotency_store: Arc<IdempotencyStore>,
+		fee_rate_estimates: Arc<FeeRateEstimates>, client_addr: SocketAddr,
+	) -> Self {
+		Self {
+			node,
+			payment_failure_reasons,
+			issued_invoices,
+			claimable_payments,
+			payment_metadata,
+			forwarded_payments,
+			channel_history,
+			spending_policy,
+			audit_log,
+			idempotency_store,
+			fee_rate_estimates,
+			client_addr,
+		}
+	}
+
+	/// Wraps the handler so that a request repeated with the same `Idempotency-Key` header returns
+	/// the original response instead of being executed again.
+	fn idempotent<T: Message, R: Message + Default, E: Into<HandlerError>>(
+		&self, path: &'static str, req: &Req,
+		handler: impl Fn(Arc<Node>, T) -> Result<R, E> + Send + 'static,
+	) -> impl Fn(Arc<Node>, T) -> Result<R, HandlerError> + Send + 'static {
+		let idempotency_store = Arc::clone(&self.idempotency_store);
+		let idempotency_key = req
+			.headers()
+			.get(IDEMPOTENCY_KEY_HEADER)
+			.map(|value| value.to_str().map(str::to_string).map_err(|_| ()));
+		move |node, request| match &idempotency_key {
+			Some(Ok(key)) => idempotency_store
+				.execute(key, path, request, |request| handler(node, request).map_err(Into::into)),
+			Some(Err(())) => {
+				Err(HandlerError::InvalidRequest("Invalid idempotency key".to_string()))
+			},
+			None => handler(node, request).map_err(Into::into),
+		}
+	}
+
+	/// Wraps the handler of a state-changing request, so that every call is recorded in the audit
+	/// log along with its outcome. Calls that can't be recorded are refused.
+	fn audited<T: Message + AuditedRequest, R, E: Into<HandlerError>>(
+		&self, path: &'static str, handler: impl Fn(Arc<Node>, T) -> Result<R, E> + Send + 'static,
+	) -> impl Fn(Arc<Node>, T) -> Result<R, HandlerError> + Send + 'static {
+		let audit_log = Arc::clone(&self.audit_log);
+		let identity = self.client_addr.to_string();
+		move |node, request| {
+			let call_sequence = audit_log.record_call(&identity, path, &request).map_err(|e| {
+				eprintln!("Failed to record call to {} in the audit log: {}", path, e);
+				ldk_node::NodeError::PersistenceFailed
+			})?;
+			let result = handler(node, request).map_err(Into::into);
+			let error = result.as_ref().err().map(HandlerError::message);
+			// The call has been handled at this point, so failing it would only prompt the caller
+			// to retry it.
+			if let Err(e) = audit_log.record_outcome(&identity, path, call_sequence, error) {
+				eprintln!("Failed to record outcome of call to {} in the audit log: {}", path, e);
+			}
+			result
+		}
 	}
 }
 
@@ -74,26 +236,323 @@ impl Service<Req> for NodeService {
 			GET_NODE_STATUS_PATH => {
 				Box::pin(handle_request(node, req, handle_get_node_status_request))
 			},
+			SIGN_MESSAGE_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(SIGN_MESSAGE_PATH, handle_sign_message_request),
+			)),
+			VERIFY_SIGNATURE_PATH => {
+				Box::pin(handle_request(node, req, handle_verify_signature_request))
+			},
 			GET_NODE_BALANCES_PATH => {
 				Box::pin(handle_request(node, req, handle_get_balances_request))
 			},
-			ONCHAIN_RECEIVE_PATH => Box::pin(handle_request(node, req, handle_onchain_receive)),
-			ONCHAIN_SEND_PATH => Box::pin(handle_request(node, req, handle_onchain_send)),
+			GET_FEE_ESTIMATES_PATH => {
+				let fee_rate_estimates = Arc::clone(&self.fee_rate_estimates);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_get_fee_estimates(node, request, &fee_rate_estimates)
+				}))
+			},
+			ONCHAIN_RECEIVE_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(ONCHAIN_RECEIVE_PATH, handle_onchain_receive),
+			)),
+			ONCHAIN_SEND_PATH => {
+				let spending_policy = Arc::clone(&self.spending_policy);
+				let fee_rate_estimates = Arc::clone(&self.fee_rate_estimates);
+				let handler = self.idempotent(ONCHAIN_SEND_PATH, &req, move |node, request| {
+					handle_onchain_send(node, request, &spending_policy, &fee_rate_estimates)
+				});
+				Box::pin(handle_request(node, req, self.audited(ONCHAIN_SEND_PATH, handler)))
+			},
+			ONCHAIN_TRANSACTIONS_PATH => {
+				let channel_history = Arc::clone(&self.channel_history);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_onchain_transactions(node, request, &channel_history)
+				}))
+			},
 			BOLT11_RECEIVE_PATH => {
-				Box::pin(handle_request(node, req, handle_bolt11_receive_request))
+				let issued_invoices = Arc::clone(&self.issued_invoices);
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT11_RECEIVE_PATH, move |node, request| {
+						handle_bolt11_receive_request(
+							node,
+							request,
+							&issued_invoices,
+							&payment_metadata,
+						)
+					}),
+				))
+			},
+			BOLT11_RECEIVE_FOR_HASH_PATH => {
+				let issued_invoices = Arc::clone(&self.issued_invoices);
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT11_RECEIVE_FOR_HASH_PATH, move |node, request| {
+						handle_bolt11_receive_for_hash_request(
+							node,
+							request,
+							&issued_invoices,
+							&payment_metadata,
+						)
+					}),
+				))
+			},
+			BOLT11_CLAIMABLE_PATH => {
+				let claimable_payments = Arc::clone(&self.claimable_payments);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_claimable_payments_request(node, request, &claimable_payments)
+				}))
+			},
+			BOLT11_CLAIM_PATH => {
+				let claimable_payments = Arc::clone(&self.claimable_payments);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT11_CLAIM_PATH, move |node, request| {
+						handle_bolt11_claim_request(node, request, &claimable_payments)
+					}),
+				))
+			},
+			BOLT11_FAIL_PATH => {
+				let claimable_payments = Arc::clone(&self.claimable_payments);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT11_FAIL_PATH, move |node, request| {
+						handle_bolt11_fail_request(node, request, &claimable_payments)
+					}),
+				))
+			},
+			BOLT11_DECODE_PATH => Box::pin(handle_request(node, req, handle_bolt11_decode_request)),
+			BOLT11_LIST_INVOICES_PATH => {
+				let issued_invoices = Arc::clone(&self.issued_invoices);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_invoices_request(node, request, &issued_invoices)
+				}))
+			},
+			BOLT11_CANCEL_PATH => {
+				let issued_invoices = Arc::clone(&self.issued_invoices);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT11_CANCEL_PATH, move |node, request| {
+						handle_bolt11_cancel_request(node, request, &issued_invoices)
+					}),
+				))
+			},
+			BOLT11_SEND_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let spending_policy = Arc::clone(&self.spending_policy);
+				let handler = self.idempotent(BOLT11_SEND_PATH, &req, move |node, request| {
+					handle_bolt11_send_request(node, request, &payment_metadata, &spending_policy)
+				});
+				Box::pin(handle_request(node, req, self.audited(BOLT11_SEND_PATH, handler)))
+			},
+			BOLT11_PROBE_PATH => Box::pin(handle_request(node, req, handle_bolt11_probe_request)),
+			BOLT12_SEND_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let spending_policy = Arc::clone(&self.spending_policy);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(BOLT12_SEND_PATH, move |node, request| {
+						handle_bolt12_send_request(
+							node,
+							request,
+							&payment_metadata,
+							&spending_policy,
+						)
+					}),
+				))
+			},
+			SPONTANEOUS_SEND_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let spending_policy = Arc::clone(&self.spending_policy);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(SPONTANEOUS_SEND_PATH, move |node, request| {
+						handle_spontaneous_send_request(
+							node,
+							request,
+							&payment_metadata,
+							&spending_policy,
+						)
+					}),
+				))
+			},
+			UNIFIED_RECEIVE_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(UNIFIED_RECEIVE_PATH, handle_unified_receive),
+			)),
+			UNIFIED_SEND_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let spending_policy = Arc::clone(&self.spending_policy);
+				let fee_rate_estimates = Arc::clone(&self.fee_rate_estimates);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(UNIFIED_SEND_PATH, move |node, request| {
+						handle_unified_send(
+							node,
+							request,
+							&payment_metadata,
+							&spending_policy,
+							&fee_rate_estimates,
+						)
+					}),
+				))
+			},
+			SPONTANEOUS_PROBE_PATH => {
+				Box::pin(handle_request(node, req, handle_spontaneous_probe_request))
+			},
+			EXPORT_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let forwarded_payments = Arc::clone(&self.forwarded_payments);
+				let channel_history = Arc::clone(&self.channel_history);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_export_request(
+						node,
+						request,
+						&payment_metadata,
+						&forwarded_payments,
+						&channel_history,
+					)
+				}))
+			},
+			LIST_FORWARDS_PATH => {
+				let forwarded_payments = Arc::clone(&self.forwarded_payments);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_forwards_request(node, request, &forwarded_payments)
+				}))
+			},
+			LIST_CLOSED_CHANNELS_PATH => {
+				let channel_history = Arc::clone(&self.channel_history);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_closed_channels_request(node, request, &channel_history)
+				}))
+			},
+			LIST_PENDING_APPROVALS_PATH => {
+				let spending_policy = Arc::clone(&self.spending_policy);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_pending_approvals_request(node, request, &spending_policy)
+				}))
+			},
+			APPROVE_PAYMENT_PATH => {
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				let spending_policy = Arc::clone(&self.spending_policy);
+				let fee_rate_estimates = Arc::clone(&self.fee_rate_estimates);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(APPROVE_PAYMENT_PATH, move |node, request| {
+						handle_approve_payment_request(
+							node,
+							request,
+							&payment_metadata,
+							&spending_policy,
+							&fee_rate_estimates,
+						)
+					}),
+				))
+			},
+			REJECT_PAYMENT_PATH => {
+				let spending_policy = Arc::clone(&self.spending_policy);
+				Box::pin(handle_request(
+					node,
+					req,
+					self.audited(REJECT_PAYMENT_PATH, move |node, request| {
+						handle_reject_payment_request(node, request, &spending_policy)
+					}),
+				))
+			},
+			LIST_AUDIT_LOG_PATH => {
+				let audit_log = Arc::clone(&self.audit_log);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_list_audit_log_request(node, request, &audit_log)
+				}))
+			},
+			VERIFY_AUDIT_LOG_PATH => {
+				let audit_log = Arc::clone(&self.audit_log);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_verify_audit_log_request(node, request, &audit_log)
+				}))
 			},
-			BOLT11_SEND_PATH => Box::pin(handle_request(node, req, handle_bolt11_send_request)),
 			LIST_CHANNELS_PATH => Box::pin(handle_request(node, req, handle_list_channels_request)),
-			OPEN_CHANNEL_PATH => Box::pin(handle_request(node, req, handle_open_channel)),
-			CLOSE_CHANNEL_PATH => Box::pin(handle_request(node, req, handle_close_channel)),
-			FORCE_CLOSE_CHANNEL_PATH => {
-				Box::pin(handle_request(node, req, handle_force_close_channel))
+			OPEN_CHANNEL_PATH => {
+				let handler = self.idempotent(OPEN_CHANNEL_PATH, &req, handle_open_channel);
+				Box::pin(handle_request(node, req, self.audited(OPEN_CHANNEL_PATH, handler)))
 			},
+			CLOSE_CHANNEL_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(CLOSE_CHANNEL_PATH, handle_close_channel),
+			)),
+			FORCE_CLOSE_CHANNEL_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(FORCE_CLOSE_CHANNEL_PATH, handle_force_close_channel),
+			)),
+			UPDATE_CHANNEL_CONFIG_PATH => Box::pin(handle_request(
+				node,
+				req,
+				self.audited(UPDATE_CHANNEL_CONFIG_PATH, handle_update_channel_config),
+			)),
 			PAYMENTS_HISTORY_PATH => {
-				Box::pin(handle_request(node, req, handle_get_payment_history_request))
+				let payment_failure_reasons = Arc::clone(&self.payment_failure_reasons);
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_get_payment_history_request(
+						node,
+						request,
+						&payment_failure_reasons,
+						&payment_metadata,
+					)
+				}))
 			},
 			GET_PAYMENT_DETAILS_PATH => {
-				Box::pin(handle_request(node, req, handle_get_payment_details_request))
+				let payment_failure_reasons = Arc::clone(&self.payment_failure_reasons);
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_get_payment_details_request(
+						node,
+						request,
+						&payment_failure_reasons,
+						&payment_metadata,
+					)
+				}))
+			},
+			GET_PAYMENTS_BY_HASH_PATH => {
+				let payment_failure_reasons = Arc::clone(&self.payment_failure_reasons);
+				let payment_metadata = Arc::clone(&self.payment_metadata);
+				Box::pin(handle_request(node, req, move |node, request| {
+					handle_get_payments_by_hash_request(
+						node,
+						request,
+						&payment_failure_reasons,
+						&payment_metadata,
+					)
+				}))
+			},
+			LIST_GRAPH_CHANNELS_PATH => {
+				Box::pin(handle_request(node, req, handle_list_graph_channels_request))
+			},
+			GET_GRAPH_CHANNEL_PATH => {
+				Box::pin(handle_request(node, req, handle_get_graph_channel_request))
+			},
+			LIST_GRAPH_NODES_PATH => {
+				Box::pin(handle_request(node, req, handle_list_graph_nodes_request))
+			},
+			GET_GRAPH_NODE_PATH => {
+				Box::pin(handle_request(node, req, handle_get_graph_node_request))
 			},
 			path => {
 				let error = format!("Unknown request: {}", path).into_bytes();
@@ -108,10 +567,48 @@ impl Service<Req> for NodeService {
 	}
 }
 
+/// An error returned by a request handler.
+#[derive(Debug)]
+pub(crate) enum HandlerError {
+	Node(ldk_node::NodeError),
+	/// The request was refused by the spending policy.
+	PolicyViolation(String),
+	InvalidRequest(String),
+	/// The request conflicts with another one currently being handled.
+	Conflict(String),
+}
+
+impl From<ldk_node::NodeError> for HandlerError {
+	fn from(e: ldk_node::NodeError) -> Self {
+		HandlerError::Node(e)
+	}
+}
+
+impl HandlerError {
+	fn status_code(&self) -> StatusCode {
+		match self {
+			HandlerError::Node(_) => StatusCode::INTERNAL_SERVER_ERROR,
+			HandlerError::PolicyViolation(_) => StatusCode::FORBIDDEN,
+			HandlerError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+			HandlerError::Conflict(_) => StatusCode::CONFLICT,
+		}
+	}
+
+	fn message(&self) -> String {
+		match self {
+			HandlerError::Node(e) => e.to_string(),
+			HandlerError::PolicyViolation(message)
+			| HandlerError::InvalidRequest(message)
+			| HandlerError::Conflict(message) => message.clone(),
+		}
+	}
+}
+
 async fn handle_request<
 	T: Message + Default,
 	R: Message,
-	F: Fn(Arc<Node>, T) -> Result<R, ldk_node::NodeError>,
+	E: Into<HandlerError>,
+	F: Fn(Arc<Node>, T) -> Result<R, E>,
 >(
 	node: Arc<Node>, request: Req, handler: F,
 ) -> Result<<NodeService as Service<Request<Incoming>>>::Response, hyper::Error> {
@@ -121,10 +618,13 @@ async fn handle_request<
 			Ok(response) => Ok(Response::builder()
 				.body(Full::new(Bytes::from(response.encode_to_vec())))
 				.unwrap()),
-			Err(e) => Ok(Response::builder()
-				.status(StatusCode::INTERNAL_SERVER_ERROR)
-				.body(Full::new(Bytes::from(e.to_string().into_bytes())))
-				.unwrap()),
+			Err(e) => {
+				let e = e.into();
+				Ok(Response::builder()
+					.status(e.status_code())
+					.body(Full::new(Bytes::from(e.message().into_bytes())))
+					.unwrap())
+			},
 		},
 		Err(_) => Ok(Response::builder()
 			.status(StatusCode::BAD_REQUEST)
@@ -141,31 +641,69 @@ fn handle_get_node_id_request(
 	Ok(response)
 }
 
+fn handle_sign_message_request(
+	node: Arc<Node>, request: SignMessageRequest,
+) -> Result<SignMessageResponse, ldk_node::NodeError> {
+	let signature = node.sign_message(&request.message);
+	let response = SignMessageResponse { signature };
+	Ok(response)
+}
+
+fn handle_verify_signature_request(
+	node: Arc<Node>, request: VerifySignatureRequest,
+) -> Result<VerifySignatureResponse, ldk_node::NodeError> {
+	let public_key = PublicKey::from_str(&request.public_key)
+		.map_err(|_| ldk_node::NodeError::InvalidPublicKey)?;
+	let valid = node.verify_signature(&request.message, &request.signature, &public_key);
+	let response = VerifySignatureResponse { valid };
+	Ok(response)
+}
+
 fn handle_get_node_status_request(
 	node: Arc<Node>, _request: GetNodeStatusRequest,
 ) -> Result<GetNodeStatusResponse, ldk_node::NodeError> {
 	let status = node.status();
 	let BestBlock { block_hash, height } = status.current_best_block;
+	let network_graph = node.network_graph();
 
 	let response = GetNodeStatusResponse {
 		public_key: node.node_id().to_string(),
 		current_best_block: Some(protos::BestBlock { block_hash: block_hash.to_string(), height }),
-		latest_wallet_sync_timestamp: status.latest_wallet_sync_timestamp,
+		latest_wallet_sync_timestamp: status.latest_lightning_wallet_sync_timestamp,
 		latest_onchain_wallet_sync_timestamp: status.latest_onchain_wallet_sync_timestamp,
 		latest_fee_rate_cache_update_timestamp: status.latest_fee_rate_cache_update_timestamp,
 		latest_rgs_snapshot_timestamp: status.latest_rgs_snapshot_timestamp,
 		latest_node_announcement_broadcast_timestamp: status
 			.latest_node_announcement_broadcast_timestamp,
+		network_graph_node_count: network_graph.list_nodes().len() as u64,
+		network_graph_channel_count: network_graph.list_channels().len() as u64,
 	};
 	Ok(response)
 }
 
 fn handle_get_payment_history_request(
-	node: Arc<Node>, _request: PaymentsHistoryRequest,
+	node: Arc<Node>, request: PaymentsHistoryRequest,
+	payment_failure_reasons: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
 ) -> Result<PaymentsHistoryResponse, ldk_node::NodeError> {
 	let payments = node.list_payments();
 	let response = protos::PaymentsHistoryResponse {
-		payments: payments.iter().map(to_payment_details_proto).collect(),
+		payments: payments
+			.iter()
+			.map(|payment| {
+				to_payment_details_proto(payment, payment_failure_reasons, payment_metadata)
+			})
+			.filter(|payment| {
+				let metadata = payment.metadata.as_ref();
+				let label_matches = request.label.is_none()
+					|| metadata.is_some_and(|m| {
+						m.labels.iter().any(|l| Some(l) == request.label.as_ref())
+					});
+				let external_id_matches = request.external_id.is_none()
+					|| request.external_id == metadata.and_then(|m| m.external_id.clone());
+				label_matches && external_id_matches
+			})
+			.collect(),
 	};
 	Ok(response)
 }
@@ -179,20 +717,171 @@ fn handle_onchain_receive(
 }
 
 fn handle_onchain_send(
-	node: Arc<Node>, request: OnchainSendRequest,
-) -> Result<OnchainSendResponse, ldk_node::NodeError> {
+	node: Arc<Node>, request: OnchainSendRequest, spending_policy: &SpendingPolicy,
+	fee_rate_estimates: &FeeRateEstimates,
+) -> Result<OnchainSendResponse, HandlerError> {
+	let amount_sats = match request.amount_sats {
+		Some(amount_sats) => amount_sats,
+		// When draining the wallet, the whole spendable balance is accounted for.
+		None => node.list_balances().spendable_onchain_balance_sats,
+	};
+	let payment = OutgoingPayment {
+		kind: OutgoingPaymentKind::Onchain,
+		amount_msat: amount_sats.saturating_mul(1000),
+		destination: request.address.clone(),
+	};
+	let response = match spending_policy.authorize(payment, request, |request| {
+		send_onchain_payment(&node, request, fee_rate_estimates)
+	})? {
+		Authorized::Sent(response) => response,
+		Authorized::Parked(approval_id) => {
+			OnchainSendResponse { approval_id: Some(approval_id), ..Default::default() }
+		},
+	};
+	Ok(response)
+}
+
+fn send_onchain_payment(
+	node: &Node, request: OnchainSendRequest, fee_rate_estimates: &FeeRateEstimates,
+) -> Result<OnchainSendResponse, HandlerError> {
 	let address = Address::from_str(&request.address)
 		.map_err(|_| ldk_node::NodeError::InvalidAddress)?
 		.require_network(node.config().network)
 		.map_err(|_| ldk_node::NodeError::InvalidAddress)?;
+	let fee_rate = match request.fee_rate {
+		Some(onchain_send_request::FeeRate::FeeRateSatPerVb(fee_rate_sat_per_vb)) => {
+			let fee_rate = FeeRate::from_sat_per_vb(fee_rate_sat_per_vb).ok_or_else(|| {
+				HandlerError::InvalidRequest(format!(
+					"Fee rate of {} sat/vB is out of range",
+					fee_rate_sat_per_vb
+				))
+			})?;
+			Some(fee_rate)
+		},
+		Some(onchain_send_request::FeeRate::ConfirmationTargetBlocks(target_blocks)) => {
+			let fee_rate = fee_rate_estimates
+				.fee_rate(target_blocks)
+				.ok_or(ldk_node::NodeError::FeerateEstimationUpdateFailed)?;
+			Some(fee_rate)
+		},
+		None => None,
+	};
+
+	let onchain_payment = node.onchain_payment();
 	let txid = match request.amount_sats {
-		Some(amount_sats) => node.onchain_payment().send_to_address(&address, amount_sats)?,
-		None => node.onchain_payment().send_all_to_address(&address)?,
+		Some(amount_sats) => onchain_payment.send_to_address(&address, amount_sats, fee_rate)?,
+		None => onchain_payment.send_all_to_address(&address, true, fee_rate)?,
+	};
+	let response = OnchainSendResponse { txid: txid.to_string(), approval_id: None };
+	Ok(response)
+}
+
+fn handle_get_fee_estimates(
+	_node: Arc<Node>, _request: GetFeeEstimatesRequest, fee_rate_estimates: &FeeRateEstimates,
+) -> Result<GetFeeEstimatesResponse, ldk_node::NodeError> {
+	let fee_rates = fee_rate_estimates
+		.list()
+		.into_iter()
+		.map(|(target_blocks, sat_per_vb)| EsploraFeeRateEstimate { target_blocks, sat_per_vb })
+		.collect();
+	let response = GetFeeEstimatesResponse {
+		fee_rates,
+		latest_update_timestamp: fee_rate_estimates.latest_update_timestamp(),
 	};
-	let response = OnchainSendResponse { txid: txid.to_string() };
 	Ok(response)
 }
 
+fn handle_onchain_transactions(
+	node: Arc<Node>, _request: ListOnchainTransactionsRequest,
+	channel_history: &JsonLinesMap<ChannelRecord>,
+) -> Result<ListOnchainTransactionsResponse, ldk_node::NodeError> {
+	let (funding_channel_ids, closing_channel_ids) = channel_ids_by_txid(&node, channel_history);
+	// The wallet records each of its transactions as an on-chain payment.
+	let transactions = node
+		.list_payments()
+		.into_iter()
+		.filter_map(|payment| match payment.kind {
+			PaymentKind::Onchain { txid, status } => Some((payment, txid, status)),
+			_ => None,
+		})
+		.map(|(payment, txid, status)| {
+			let amount_sats = payment.amount_msat.unwrap_or(0) / 1000;
+			// The wallet only knows the fee of transactions spending its own outputs.
+			let fee_sats = match payment.direction {
+				PaymentDirection::Outbound => payment.fee_paid_msat.map(|fee_msat| fee_msat / 1000),
+				PaymentDirection::Inbound => None,
+			};
+			let net_amount_sats = match payment.direction {
+				PaymentDirection::Outbound => -((amount_sats + fee_sats.unwrap_or(0)) as i64),
+				PaymentDirection::Inbound => amount_sats as i64,
+			};
+			let (confirmation_height, confirmation_timestamp) = match status {
+				ConfirmationStatus::Confirmed { height, timestamp, .. } => {
+					(Some(height), Some(timestamp))
+				},
+				ConfirmationStatus::Unconfirmed => (None, None),
+			};
+			OnchainTransaction {
+				txid: txid.to_string(),
+				net_amount_sats,
+				fee_sats,
+				confirmation_height,
+				confirmation_timestamp,
+				channel_id: funding_channel_ids
+					.get(&txid)
+					.or_else(|| closing_channel_ids.get(&txid))
+					.cloned(),
+			}
+		})
+		.collect();
+
+	let response = ListOnchainTransactionsResponse { transactions };
+	Ok(response)
+}
+
+/// Returns the channels funded by and swept from transactions, keyed by txid.
+///
+/// We relate transactions to channels via the funding transactions of our open channels, the
+/// sweeps of channels that have been closed and our channel history.
+fn channel_ids_by_txid(
+	node: &Node, channel_history: &JsonLinesMap<ChannelRecord>,
+) -> (HashMap<Txid, String>, HashMap<Txid, String>) {
+	let mut funding_channel_ids = HashMap::new();
+	for channel in node.list_channels() {
+		if let Some(funding_txo) = channel.funding_txo {
+			funding_channel_ids.insert(funding_txo.txid, channel.channel_id.to_string());
+		}
+	}
+	let mut closing_channel_ids = HashMap::new();
+	for balance in node.list_balances().pending_balances_from_channel_closures {
+		match balance {
+			BroadcastAwaitingConfirmation {
+				channel_id: Some(channel_id),
+				latest_spending_txid,
+				..
+			}
+			| AwaitingThresholdConfirmations {
+				channel_id: Some(channel_id),
+				latest_spending_txid,
+				..
+			} => {
+				closing_channel_ids.insert(latest_spending_txid, channel_id.to_string());
+			},
+			_ => {},
+		}
+	}
+	for record in channel_history.list() {
+		let parse_txid = |txid: &Option<String>| txid.as_ref().and_then(|txid| txid.parse().ok());
+		if let Some(funding_txid) = parse_txid(&record.funding_txid) {
+			funding_channel_ids.entry(funding_txid).or_insert_with(|| record.channel_id.clone());
+		}
+		if let Some(closing_txid) = parse_txid(&record.closing_txid) {
+			closing_channel_ids.entry(closing_txid).or_insert_with(|| record.channel_id.clone());
+		}
+	}
+	(funding_channel_ids, closing_channel_ids)
+}
+
 fn handle_get_balances_request(
 	node: Arc<Node>, _request: GetBalancesRequest,
 ) -> Result<GetBalancesResponse, ldk_node::NodeError> {
@@ -201,22 +890,23 @@ fn handle_get_balances_request(
 		.lightning_balances
 		.into_iter()
 		.map(|lightning_balance| match lightning_balance {
-			ClaimableOnChannelClose { channel_id, counterparty_node_id, amount_satoshis } => {
-				protos::LightningBalance {
-					balance_type: Some(lightning_balance::BalanceType::ClaimableOnChannelClose(
-						protos::ClaimableOnChannelClose {
-							channel_id: channel_id.to_string(),
-							counterparty_node_id: counterparty_node_id.to_string(),
-							amount_satoshis,
-						},
-					)),
-				}
+			ClaimableOnChannelClose {
+				channel_id, counterparty_node_id, amount_satoshis, ..
+			} => protos::LightningBalance {
+				balance_type: Some(lightning_balance::BalanceType::ClaimableOnChannelClose(
+					protos::ClaimableOnChannelClose {
+						channel_id: channel_id.to_string(),
+						counterparty_node_id: counterparty_node_id.to_string(),
+						amount_satoshis,
+					},
+				)),
 			},
 			ClaimableAwaitingConfirmations {
 				channel_id,
 				counterparty_node_id,
 				amount_satoshis,
 				confirmation_height,
+				..
 			} => protos::LightningBalance {
 				balance_type: Some(lightning_balance::BalanceType::ClaimableAwaitingConfirmations(
 					protos::ClaimableAwaitingConfirmations {
@@ -252,6 +942,7 @@ fn handle_get_balances_request(
 				amount_satoshis,
 				claimable_height,
 				payment_hash,
+				..
 			} => protos::LightningBalance {
 				balance_type: Some(lightning_balance::BalanceType::MaybeTimeoutClaimableHtlc(
 					protos::MaybeTimeoutClaimableHtlc {
@@ -359,33 +1050,722 @@ fn handle_get_balances_request(
 }
 
 fn handle_bolt11_receive_request(
-	node: Arc<Node>, request: Bolt11ReceiveRequest,
+	node: Arc<Node>, request: Bolt11ReceiveRequest, issued_invoices: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
 ) -> Result<Bolt11ReceiveResponse, ldk_node::NodeError> {
+	let description = invoice_description(&request.description)?;
 	let invoice = match request.amount_msat {
 		Some(amount_msat) => {
-			node.bolt11_payment().receive(amount_msat, &request.description, request.expiry_secs)?
+			node.bolt11_payment().receive(amount_msat, &description, request.expiry_secs)?
 		},
-		None => node
-			.bolt11_payment()
-			.receive_variable_amount(&request.description, request.expiry_secs)?,
+		None => node.bolt11_payment().receive_variable_amount(&description, request.expiry_secs)?,
 	};
 
+	let payment_hash = invoice_payment_hash(&invoice);
+	if let Err(e) = issued_invoices.insert(payment_hash.to_string(), invoice.to_string()) {
+		eprintln!("Failed to persist issued invoice {}: {}", payment_hash, e);
+	}
+	store_payment_metadata(payment_metadata, &PaymentId(payment_hash.0), request.metadata);
+
 	let response = Bolt11ReceiveResponse { invoice: invoice.to_string() };
 	Ok(response)
 }
 
+fn invoice_description(description: &str) -> Result<Bolt11InvoiceDescription, ldk_node::NodeError> {
+	let description = Description::new(description.to_string())
+		.map_err(|_| ldk_node::NodeError::InvoiceCreationFailed)?;
+	Ok(Bolt11InvoiceDescription::Direct(description))
+}
+
+fn handle_bolt11_receive_for_hash_request(
+	node: Arc<Node>, request: Bolt11ReceiveForHashRequest, issued_invoices: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> Result<Bolt11ReceiveForHashResponse, ldk_node::NodeError> {
+	let payment_hash = parse_payment_hash(&request.payment_hash)?;
+	let description = invoice_description(&request.description)?;
+	let invoice = match request.amount_msat {
+		Some(amount_msat) => node.bolt11_payment().receive_for_hash(
+			amount_msat,
+			&description,
+			request.expiry_secs,
+			payment_hash,
+		)?,
+		None => node.bolt11_payment().receive_variable_amount_for_hash(
+			&description,
+			request.expiry_secs,
+			payment_hash,
+		)?,
+	};
+
+	if let Err(e) = issued_invoices.insert(payment_hash.to_string(), invoice.to_string()) {
+		eprintln!("Failed to persist issued invoice {}: {}", payment_hash, e);
+	}
+	store_payment_metadata(payment_metadata, &PaymentId(payment_hash.0), request.metadata);
+
+	let response = Bolt11ReceiveForHashResponse { invoice: invoice.to_string() };
+	Ok(response)
+}
+
+fn handle_list_claimable_payments_request(
+	node: Arc<Node>, _request: ListClaimablePaymentsRequest,
+	claimable_payments: &JsonFileStore<ClaimablePayment>,
+) -> Result<ListClaimablePaymentsResponse, ldk_node::NodeError> {
+	// Payments are failed back once their claim deadline is reached, even if we missed the event.
+	let best_block_height = node.status().current_best_block.height;
+	let payments = claimable_payments
+		.list_entries()
+		.into_iter()
+		.filter(|(_, payment)| {
+			payment.claim_deadline.map_or(true, |claim_deadline| claim_deadline > best_block_height)
+		})
+		.map(|(payment_hash, payment)| protos::ClaimablePayment {
+			payment_hash,
+			claimable_amount_msat: payment.claimable_amount_msat,
+			claim_deadline: payment.claim_deadline,
+		})
+		.collect();
+
+	let response = ListClaimablePaymentsResponse { payments };
+	Ok(response)
+}
+
+fn handle_bolt11_claim_request(
+	node: Arc<Node>, request: Bolt11ClaimRequest,
+	claimable_payments: &JsonFileStore<ClaimablePayment>,
+) -> Result<Bolt11ClaimResponse, ldk_node::NodeError> {
+	let payment_hash = parse_payment_hash(&request.payment_hash)?;
+	let preimage = <[u8; 32]>::from_hex(&request.preimage)
+		.map(PaymentPreimage)
+		.map_err(|_| ldk_node::NodeError::InvalidPaymentPreimage)?;
+	let claimable_amount_msat = match request.claimable_amount_msat {
+		Some(claimable_amount_msat) => claimable_amount_msat,
+		None => {
+			claimable_payments
+				.get(&payment_hash.to_string())
+				.ok_or(ldk_node::NodeError::InvalidPaymentHash)?
+				.claimable_amount_msat
+		},
+	};
+	node.bolt11_payment().claim_for_hash(payment_hash, claimable_amount_msat, preimage)?;
+	forget_claimable_payment(claimable_payments, &payment_hash);
+
+	let response = Bolt11ClaimResponse {};
+	Ok(response)
+}
+
+fn handle_bolt11_fail_request(
+	node: Arc<Node>, request: Bolt11FailRequest,
+	claimable_payments: &JsonFileStore<ClaimablePayment>,
+) -> Result<Bolt11FailResponse, ldk_node::NodeError> {
+	let payment_hash = parse_payment_hash(&request.payment_hash)?;
+	node.bolt11_payment().fail_for_hash(payment_hash)?;
+	forget_claimable_payment(claimable_payments, &payment_hash);
+
+	let response = Bolt11FailResponse {};
+	Ok(response)
+}
+
+pub(crate) fn forget_claimable_payment(
+	claimable_payments: &JsonFileStore<ClaimablePayment>, payment_hash: &PaymentHash,
+) {
+	if let Err(e) = claimable_payments.remove(&payment_hash.to_string()) {
+		eprintln!("Failed to remove claimable payment {}: {}", payment_hash, e);
+	}
+}
+
+fn handle_bolt11_decode_request(
+	_node: Arc<Node>, request: Bolt11DecodeRequest,
+) -> Result<Bolt11DecodeResponse, ldk_node::NodeError> {
+	let invoice = Bolt11Invoice::from_str(&request.invoice)
+		.map_err(|_| ldk_node::NodeError::InvalidInvoice)?;
+	let description = match invoice.description() {
+		Bolt11InvoiceDescriptionRef::Direct(description) => {
+			bolt11_decode_response::Description::DescriptionText(description.to_string())
+		},
+		Bolt11InvoiceDescriptionRef::Hash(hash) => {
+			bolt11_decode_response::Description::DescriptionHash(hash.0.to_string())
+		},
+	};
+
+	let response = Bolt11DecodeResponse {
+		payment_hash: invoice_payment_hash(&invoice).to_string(),
+		amount_msat: invoice.amount_milli_satoshis(),
+		description: Some(description),
+		payee_node_id: invoice.recover_payee_pub_key().to_string(),
+		timestamp: invoice.duration_since_epoch().as_secs(),
+		expiry_secs: invoice.expiry_time().as_secs(),
+		is_expired: invoice.is_expired(),
+		min_final_cltv_expiry_delta: invoice.min_final_cltv_expiry_delta(),
+	};
+	Ok(response)
+}
+
+fn handle_list_invoices_request(
+	node: Arc<Node>, request: ListInvoicesRequest, issued_invoices: &JsonFileStore<String>,
+) -> Result<ListInvoicesResponse, ldk_node::NodeError> {
+	let mut invoices: Vec<protos::Invoice> = issued_invoices
+		.list()
+		.iter()
+		.filter_map(|invoice| Bolt11Invoice::from_str(invoice).ok())
+		.map(|invoice| {
+			let created_at = invoice.duration_since_epoch();
+			protos::Invoice {
+				invoice: invoice.to_string(),
+				payment_hash: invoice_payment_hash(&invoice).to_string(),
+				amount_msat: invoice.amount_milli_satoshis(),
+				created_at: created_at.as_secs(),
+				expires_at: (created_at + invoice.expiry_time()).as_secs(),
+				status: invoice_status(&node, &invoice).into(),
+			}
+		})
+		.filter(|invoice| request.status.is_none() || request.status == Some(invoice.status))
+		.collect();
+	invoices.sort_by_key(|invoice| invoice.created_at);
+
+	let response = ListInvoicesResponse { invoices };
+	Ok(response)
+}
+
+fn handle_bolt11_cancel_request(
+	node: Arc<Node>, request: Bolt11CancelRequest, issued_invoices: &JsonFileStore<String>,
+) -> Result<Bolt11CancelResponse, ldk_node::NodeError> {
+	let payment_hash = parse_payment_hash(&request.payment_hash)?;
+	let invoice = issued_invoices
+		.get(&payment_hash.to_string())
+		.and_then(|invoice| Bolt11Invoice::from_str(&invoice).ok())
+		.ok_or(ldk_node::NodeError::InvalidPaymentHash)?;
+	match invoice_status(&node, &invoice) {
+		InvoiceStatus::Open | InvoiceStatus::Expired => {
+			node.bolt11_payment().fail_for_hash(payment_hash)?
+		},
+		InvoiceStatus::Paid | InvoiceStatus::Cancelled => {
+			return Err(ldk_node::NodeError::InvalidPaymentHash)
+		},
+	}
+
+	let response = Bolt11CancelResponse {};
+	Ok(response)
+}
+
+fn invoice_payment_hash(invoice: &Bolt11Invoice) -> PaymentHash {
+	PaymentHash(invoice.payment_hash().to_byte_array())
+}
+
+fn parse_payment_hash(payment_hash: &str) -> Result<PaymentHash, ldk_node::NodeError> {
+	<[u8; 32]>::from_hex(payment_hash)
+		.map(PaymentHash)
+		.map_err(|_| ldk_node::NodeError::InvalidPaymentHash)
+}
+
+/// Derives the state of an invoice we issued from the inbound payment tracked for it, which
+/// shares its id with the invoice's payment hash.
+fn invoice_status(node: &Node, invoice: &Bolt11Invoice) -> InvoiceStatus {
+	let payment_id = PaymentId(invoice_payment_hash(invoice).0);
+	match node.payment(&payment_id).map(|payment| payment.status) {
+		Some(PaymentStatus::Succeeded) => InvoiceStatus::Paid,
+		Some(PaymentStatus::Failed) => InvoiceStatus::Cancelled,
+		_ if invoice.is_expired() => InvoiceStatus::Expired,
+		_ => InvoiceStatus::Open,
+	}
+}
+
 fn handle_bolt11_send_request(
-	node: Arc<Node>, request: Bolt11SendRequest,
-) -> Result<Bolt11SendResponse, ldk_node::NodeError> {
+	node: Arc<Node>, request: Bolt11SendRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+	spending_policy: &SpendingPolicy,
+) -> Result<Bolt11SendResponse, HandlerError> {
 	let invoice = Bolt11Invoice::from_str(&request.invoice)
 		.map_err(|_| ldk_node::NodeError::InvalidInvoice)?;
+	let payment = OutgoingPayment {
+		kind: OutgoingPaymentKind::Bolt11,
+		amount_msat: request
+			.amount_msat
+			.or(invoice.amount_milli_satoshis())
+			.ok_or(ldk_node::NodeError::InvalidAmount)?,
+		destination: invoice.recover_payee_pub_key().to_string(),
+	};
+	let response = match spending_policy.authorize(payment, request, |request| {
+		send_bolt11_payment(&node, request, payment_metadata)
+	})? {
+		Authorized::Sent(response) => response,
+		Authorized::Parked(approval_id) => {
+			Bolt11SendResponse { payment_id: None, approval_id: Some(approval_id) }
+		},
+	};
+	Ok(response)
+}
+
+fn send_bolt11_payment(
+	node: &Node, request: Bolt11SendRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> Result<Bolt11SendResponse, HandlerError> {
+	let invoice = Bolt11Invoice::from_str(&request.invoice)
+		.map_err(|_| ldk_node::NodeError::InvalidInvoice)?;
+	let amount_msat = request.amount_msat.or(invoice.amount_milli_satoshis());
+	let sending_parameters = request
+		.sending_parameters
+		.map(|sending_parameters| to_sending_parameters(sending_parameters, amount_msat))
+		.transpose()?;
 	let payment_id = match request.amount_msat {
-		Some(amount_msat) => node.bolt11_payment().send_using_amount(&invoice, amount_msat)?,
-		None => node.bolt11_payment().send(&invoice)?,
+		Some(amount_msat) => {
+			node.bolt11_payment().send_using_amount(&invoice, amount_msat, sending_parameters)?
+		},
+		None => node.bolt11_payment().send(&invoice, sending_parameters)?,
 	};
+	store_payment_metadata(payment_metadata, &payment_id, request.metadata);
 
-	let response =
-		Bolt11SendResponse { payment_id: Some(protos::PaymentId { data: payment_id.0.to_vec() }) };
+	let response = Bolt11SendResponse {
+		payment_id: Some(protos::PaymentId { data: payment_id.0.to_vec() }),
+		approval_id: None,
+	};
+	Ok(response)
+}
+
+fn handle_bolt12_send_request(
+	node: Arc<Node>, request: Bolt12SendRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+	spending_policy: &SpendingPolicy,
+) -> Result<Bolt12SendResponse, HandlerError> {
+	let offer = Offer::from_str(&request.offer).map_err(|_| ldk_node::NodeError::InvalidOffer)?;
+	let payment = OutgoingPayment {
+		kind: OutgoingPaymentKind::Bolt12,
+		amount_msat: offer_amount_msat(&offer, &request)
+			.ok_or(ldk_node::NodeError::InvalidAmount)?,
+		// Offers that hide the issuer behind blinded paths are identified by the offer itself.
+		destination: offer
+			.issuer_signing_pubkey()
+			.map(|pubkey| pubkey.to_string())
+			.unwrap_or_else(|| offer.to_string()),
+	};
+	let response = match spending_policy.authorize(payment, request, |request| {
+		send_bolt12_payment(&node, request, payment_metadata)
+	})? {
+		Authorized::Sent(response) => response,
+		Authorized::Parked(approval_id) => {
+			Bolt12SendResponse { payment_id: None, approval_id: Some(approval_id) }
+		},
+	};
+	Ok(response)
+}
+
+/// The amount to pay for the offer, if given by the request or in bitcoin by the offer.
+fn offer_amount_msat(offer: &Offer, request: &Bolt12SendRequest) -> Option<u64> {
+	request.amount_msat.or(match offer.amount() {
+		Some(Amount::Bitcoin { amount_msats }) => Some(amount_msats),
+		_ => None,
+	})
+}
+
+fn send_bolt12_payment(
+	node: &Node, request: Bolt12SendRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> Result<Bolt12SendResponse, ldk_node::NodeError> {
+	let offer = Offer::from_str(&request.offer).map_err(|_| ldk_node::NodeError::InvalidOffer)?;
+	let payment_id = match request.amount_msat {
+		Some(amount_msat) => node.bolt12_payment().send_using_amount(
+			&offer,
+			amount_msat,
+			None,
+			request.payer_note,
+		)?,
+		None => node.bolt12_payment().send(&offer, None, request.payer_note)?,
+	};
+	store_payment_metadata(payment_metadata, &payment_id, request.metadata);
+
+	let response = Bolt12SendResponse {
+		payment_id: Some(protos::PaymentId { data: payment_id.0.to_vec() }),
+		approval_id: None,
+	};
+	Ok(response)
+}
+
+fn handle_spontaneous_send_request(
+	node: Arc<Node>, request: SpontaneousSendRequest,
+	payment_metadata: &JsonFileStore<PaymentMetadata>, spending_policy: &SpendingPolicy,
+) -> Result<SpontaneousSendResponse, HandlerError> {
+	let node_id =
+		PublicKey::from_str(&request.node_id).map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
+	let payment = OutgoingPayment {
+		kind: OutgoingPaymentKind::Spontaneous,
+		amount_msat: request.amount_msat,
+		destination: node_id.to_string(),
+	};
+	let response = match spending_policy.authorize(payment, request, |request| {
+		send_spontaneous_payment(&node, request, payment_metadata)
+	})? {
+		Authorized::Sent(response) => response,
+		Authorized::Parked(approval_id) => {
+			SpontaneousSendResponse { payment_id: None, approval_id: Some(approval_id) }
+		},
+	};
+	Ok(response)
+}
+
+fn send_spontaneous_payment(
+	node: &Node, request: SpontaneousSendRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> Result<SpontaneousSendResponse, HandlerError> {
+	let node_id =
+		PublicKey::from_str(&request.node_id).map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
+	let sending_parameters = request
+		.sending_parameters
+		.map(|sending_parameters| {
+			to_sending_parameters(sending_parameters, Some(request.amount_msat))
+		})
+		.transpose()?;
+	let payment_id =
+		node.spontaneous_payment().send(request.amount_msat, node_id, sending_parameters)?;
+	store_payment_metadata(payment_metadata, &payment_id, request.metadata);
+
+	let response = SpontaneousSendResponse {
+		payment_id: Some(protos::PaymentId { data: payment_id.0.to_vec() }),
+		approval_id: None,
+	};
+	Ok(response)
+}
+
+fn handle_unified_receive(
+	node: Arc<Node>, request: UnifiedReceiveRequest,
+) -> Result<UnifiedReceiveResponse, ldk_node::NodeError> {
+	let uri = node.unified_qr_payment().receive(
+		request.amount_sats,
+		&request.description,
+		request.expiry_secs,
+	)?;
+	let response = UnifiedReceiveResponse { uri };
+	Ok(response)
+}
+
+fn handle_unified_send(
+	node: Arc<Node>, request: UnifiedSendRequest,
+	payment_metadata: &JsonFileStore<PaymentMetadata>, spending_policy: &SpendingPolicy,
+	fee_rate_estimates: &FeeRateEstimates,
+) -> Result<UnifiedSendResponse, HandlerError> {
+	let uri = payment_uri::parse(&request.uri)?;
+	let address = uri
+		.address
+		.require_network(node.config().network)
+		.map_err(|_| ldk_node::NodeError::InvalidNetwork)?;
+
+	// Each payment method is sent via its own handler so that the spending policy applies to it.
+	// Like LDK Node, we fall back to the next method if sending fails, but not if the policy
+	// refuses the payment, as the next method would pay the same recipient.
+	if let Some(offer) = uri.extras.bolt12_offer {
+		let request = Bolt12SendRequest { offer, ..Default::default() };
+		match handle_bolt12_send_request(
+			Arc::clone(&node),
+			request,
+			payment_metadata,
+			spending_policy,
+		) {
+			Ok(response) => {
+				let payment =
+					response.payment_id.map(unified_send_response::Payment::Bolt12PaymentId);
+				return Ok(UnifiedSendResponse { payment, approval_id: response.approval_id });
+			},
+			Err(HandlerError::Node(e)) => {
+				eprintln!("Failed to pay BOLT12 offer of unified URI, falling back: {}", e)
+			},
+			Err(e) => return Err(e),
+		}
+	}
+	if let Some(invoice) = uri.extras.bolt11_invoice {
+		let request = Bolt11SendRequest { invoice, ..Default::default() };
+		match handle_bolt11_send_request(
+			Arc::clone(&node),
+			request,
+			payment_metadata,
+			spending_policy,
+		) {
+			Ok(response) => {
+				let payment =
+					response.payment_id.map(unified_send_response::Payment::Bolt11PaymentId);
+				return Ok(UnifiedSendResponse { payment, approval_id: response.approval_id });
+			},
+			Err(HandlerError::Node(e)) => {
+				eprintln!("Failed to pay BOLT11 invoice of unified URI, falling back: {}", e)
+			},
+			Err(e) => return Err(e),
+		}
+	}
+
+	let amount = uri.amount.ok_or(ldk_node::NodeError::InvalidAmount)?;
+	let request = OnchainSendRequest {
+		address: address.to_string(),
+		amount_sats: Some(amount.to_sat()),
+		fee_rate: None,
+	};
+	let response = handle_onchain_send(node, request, spending_policy, fee_rate_estimates)?;
+	let payment = match response.approval_id {
+		Some(_) => None,
+		None => Some(unified_send_response::Payment::Txid(response.txid)),
+	};
+	let response = UnifiedSendResponse { payment, approval_id: response.approval_id };
+	Ok(response)
+}
+
+impl SentPayment for OnchainSendResponse {
+	fn payment_id(&self) -> String {
+		self.txid.clone()
+	}
+}
+
+impl SentPayment for Bolt11SendResponse {
+	fn payment_id(&self) -> String {
+		sent_payment_id(&self.payment_id)
+	}
+}
+
+impl SentPayment for Bolt12SendResponse {
+	fn payment_id(&self) -> String {
+		sent_payment_id(&self.payment_id)
+	}
+}
+
+impl SentPayment for SpontaneousSendResponse {
+	fn payment_id(&self) -> String {
+		sent_payment_id(&self.payment_id)
+	}
+}
+
+impl SentPayment for approve_payment_response::Payment {
+	fn payment_id(&self) -> String {
+		match self {
+			approve_payment_response::Payment::Bolt11(response) => response.payment_id(),
+			approve_payment_response::Payment::Bolt12(response) => response.payment_id(),
+			approve_payment_response::Payment::Spontaneous(response) => response.payment_id(),
+			approve_payment_response::Payment::Onchain(response) => response.payment_id(),
+		}
+	}
+}
+
+/// The id of a sent Lightning payment, in the format the node's events refer to it by.
+fn sent_payment_id(payment_id: &Option<protos::PaymentId>) -> String {
+	payment_id.as_ref().map(|payment_id| utils::to_hex(&payment_id.data)).unwrap_or_default()
+}
+
+/// Converts the requested routing limits, resolving a proportional fee limit against the amount
+/// being sent, if known.
+fn to_sending_parameters(
+	sending_parameters: protos::SendingParameters, amount_msat: Option<u64>,
+) -> Result<SendingParameters, HandlerError> {
+	let max_proportional_routing_fee_msat = sending_parameters
+		.max_total_routing_fee_ppm
+		.zip(amount_msat)
+		.map(|(ppm, amount_msat)| amount_msat.saturating_mul(ppm) / 1_000_000);
+	let max_total_routing_fee_msat =
+		match (sending_parameters.max_total_routing_fee_msat, max_proportional_routing_fee_msat) {
+			(Some(absolute), Some(proportional)) => Some(absolute.min(proportional)),
+			(absolute, proportional) => absolute.or(proportional),
+		};
+	let max_path_count = sending_parameters
+		.max_path_count
+		.map(|max_path_count| {
+			u8::try_from(max_path_count).map_err(|_| {
+				HandlerError::InvalidRequest(format!(
+					"Maximum path count of {} is out of range",
+					max_path_count
+				))
+			})
+		})
+		.transpose()?;
+
+	Ok(SendingParameters {
+		max_total_routing_fee_msat: max_total_routing_fee_msat.map(Some),
+		max_total_cltv_expiry_delta: sending_parameters.max_total_cltv_expiry_delta,
+		max_path_count,
+		max_channel_saturation_power_of_half: None,
+	})
+}
+
+fn handle_bolt11_probe_request(
+	node: Arc<Node>, request: Bolt11ProbeRequest,
+) -> Result<Bolt11ProbeResponse, ldk_node::NodeError> {
+	let invoice = Bolt11Invoice::from_str(&request.invoice)
+		.map_err(|_| ldk_node::NodeError::InvalidInvoice)?;
+	let bolt11_payment = node.bolt11_payment();
+	let probe_result = match request.amount_msat {
+		Some(amount_msat) => bolt11_payment.send_probes_using_amount(&invoice, amount_msat),
+		None => bolt11_payment.send_probes(&invoice),
+	};
+	let route_found = probe_sent(probe_result)?;
+
+	let response = Bolt11ProbeResponse { route_found };
+	Ok(response)
+}
+
+fn handle_spontaneous_probe_request(
+	node: Arc<Node>, request: SpontaneousProbeRequest,
+) -> Result<SpontaneousProbeResponse, ldk_node::NodeError> {
+	let node_id =
+		PublicKey::from_str(&request.node_id).map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
+	let probe_result = node.spontaneous_payment().send_probes(request.amount_msat, node_id);
+	let route_found = probe_sent(probe_result)?;
+
+	let response = SpontaneousProbeResponse { route_found };
+	Ok(response)
+}
+
+/// Probes fail to be sent if no route to the destination can be found, which we report rather
+/// than treat as an error.
+fn probe_sent(probe_result: Result<(), ldk_node::NodeError>) -> Result<bool, ldk_node::NodeError> {
+	match probe_result {
+		Ok(()) => Ok(true),
+		Err(ldk_node::NodeError::ProbeSendingFailed) => Ok(false),
+		Err(e) => Err(e),
+	}
+}
+
+fn handle_list_pending_approvals_request(
+	_node: Arc<Node>, _request: ListPendingApprovalsRequest, spending_policy: &SpendingPolicy,
+) -> Result<ListPendingApprovalsResponse, ldk_node::NodeError> {
+	let mut payments = spending_policy
+		.list_parked_payments()
+		.into_iter()
+		.map(|(id, parked_payment)| PendingApproval {
+			id,
+			kind: parked_payment.kind.as_str().to_string(),
+			amount_msat: parked_payment.amount_msat,
+			destination: parked_payment.destination,
+			created_at: parked_payment.created_at,
+		})
+		.collect::<Vec<_>>();
+	payments.sort_unstable_by_key(|payment| payment.created_at);
+
+	let response = ListPendingApprovalsResponse { payments };
+	Ok(response)
+}
+
+fn handle_approve_payment_request(
+	node: Arc<Node>, request: ApprovePaymentRequest,
+	payment_metadata: &JsonFileStore<PaymentMetadata>, spending_policy: &SpendingPolicy,
+	fee_rate_estimates: &FeeRateEstimates,
+) -> Result<ApprovePaymentResponse, HandlerError> {
+	let payment = spending_policy.approve(
+		&request.id,
+		&request.approval_token,
+		|parked_payment| -> Result<_, HandlerError> {
+			fn invalid_request<E>(_: E) -> HandlerError {
+				HandlerError::InvalidRequest("Failed to decode parked payment".to_string())
+			}
+			let bytes = Vec::<u8>::from_hex(&parked_payment.request).map_err(invalid_request)?;
+			let payment = match parked_payment.kind {
+				OutgoingPaymentKind::Bolt11 => {
+					let request = Bolt11SendRequest::decode(&*bytes).map_err(invalid_request)?;
+					approve_payment_response::Payment::Bolt11(send_bolt11_payment(
+						&node,
+						request,
+						payment_metadata,
+					)?)
+				},
+				OutgoingPaymentKind::Bolt12 => {
+					let request = Bolt12SendRequest::decode(&*bytes).map_err(invalid_request)?;
+					approve_payment_response::Payment::Bolt12(send_bolt12_payment(
+						&node,
+						request,
+						payment_metadata,
+					)?)
+				},
+				OutgoingPaymentKind::Spontaneous => {
+					let request =
+						SpontaneousSendRequest::decode(&*bytes).map_err(invalid_request)?;
+					approve_payment_response::Payment::Spontaneous(send_spontaneous_payment(
+						&node,
+						request,
+						payment_metadata,
+					)?)
+				},
+				OutgoingPaymentKind::Onchain => {
+					let request = OnchainSendRequest::decode(&*bytes).map_err(invalid_request)?;
+					approve_payment_response::Payment::Onchain(send_onchain_payment(
+						&node,
+						request,
+						fee_rate_estimates,
+					)?)
+				},
+			};
+			Ok(payment)
+		},
+	)?;
+
+	let response = ApprovePaymentResponse { payment: Some(payment) };
+	Ok(response)
+}
+
+fn handle_reject_payment_request(
+	_node: Arc<Node>, request: RejectPaymentRequest, spending_policy: &SpendingPolicy,
+) -> Result<RejectPaymentResponse, HandlerError> {
+	spending_policy.reject(&request.id, &request.approval_token)?;
+	let response = RejectPaymentResponse {};
+	Ok(response)
+}
+
+fn handle_list_audit_log_request(
+	_node: Arc<Node>, request: ListAuditLogRequest, audit_log: &AuditLog,
+) -> Result<ListAuditLogResponse, ldk_node::NodeError> {
+	let limit = request.limit.unwrap_or(DEFAULT_AUDIT_LOG_PAGE_LIMIT);
+	let entries = audit_log
+		.list(request.offset as usize, limit as usize)
+		.into_iter()
+		.map(|entry| AuditLogEntry {
+			sequence: entry.sequence,
+			timestamp: entry.timestamp,
+			identity: entry.identity,
+			path: entry.path,
+			request: entry.request,
+			error: entry.error,
+			prev_hash: entry.prev_hash,
+			hash: entry.hash,
+			outcome_of: entry.outcome_of,
+		})
+		.collect();
+
+	let response = ListAuditLogResponse { entries, total_count: audit_log.len() as u64 };
+	Ok(response)
+}
+
+fn handle_verify_audit_log_request(
+	_node: Arc<Node>, _request: VerifyAuditLogRequest, audit_log: &AuditLog,
+) -> Result<VerifyAuditLogResponse, ldk_node::NodeError> {
+	let verification = audit_log.verify();
+	let response = VerifyAuditLogResponse {
+		valid: verification.first_invalid_sequence.is_none(),
+		entry_count: verification.entry_count,
+		head_hash: verification.head_hash,
+		first_invalid_sequence: verification.first_invalid_sequence,
+	};
+	Ok(response)
+}
+
+fn handle_list_closed_channels_request(
+	_node: Arc<Node>, _request: ListClosedChannelsRequest,
+	channel_history: &JsonLinesMap<ChannelRecord>,
+) -> Result<ListClosedChannelsResponse, ldk_node::NodeError> {
+	let mut channels = channel_history
+		.list_entries()
+		.into_iter()
+		.filter_map(|(user_channel_id, record)| {
+			let funding_txo = match (record.funding_txid, record.funding_output_index) {
+				(Some(txid), Some(vout)) => Some(Outpoint { txid, vout }),
+				_ => None,
+			};
+			Some(ClosedChannel {
+				channel_id: record.channel_id,
+				user_channel_id,
+				counterparty_node_id: record.counterparty_node_id,
+				funding_txo,
+				channel_value_sats: record.channel_value_sats,
+				is_outbound: record.is_outbound,
+				pending_at: record.pending_at,
+				ready_at: record.ready_at,
+				closed_at: record.closed_at?,
+				closure_reason: record.closure_reason,
+				closing_txid: record.closing_txid,
+				final_balance_sats: record.final_balance_sats.unwrap_or_default(),
+				final_pending_htlc_balance_sats: record
+					.final_pending_htlc_balance_sats
+					.unwrap_or_default(),
+			})
+		})
+		.collect::<Vec<_>>();
+	channels.sort_unstable_by_key(|channel| Reverse(channel.closed_at));
+
+	let response = ListClosedChannelsResponse { channels };
 	Ok(response)
 }
 
@@ -408,17 +1788,16 @@ fn handle_list_channels_request(
 			is_outbound: c.is_outbound,
 			is_channel_ready: c.is_channel_ready,
 			is_usable: c.is_usable,
-			is_public: c.is_public,
+			is_public: c.is_announced,
 			cltv_expiry_delta: c.cltv_expiry_delta.map(|cltv| cltv as u32),
 			counterparty_outbound_htlc_minimum_msat: c.counterparty_outbound_htlc_minimum_msat,
 			counterparty_outbound_htlc_maximum_msat: c.counterparty_outbound_htlc_maximum_msat,
 			next_outbound_htlc_limit_msat: c.next_outbound_htlc_limit_msat,
 			next_outbound_htlc_minimum_msat: c.next_outbound_htlc_minimum_msat,
 			force_close_spend_delay: c.force_close_spend_delay.map(|delay| delay as u32),
-			forwarding_fee_proportional_millionths: c
-				.config
-				.forwarding_fee_proportional_millionths(),
-			forwarding_fee_base_msat: c.config.forwarding_fee_base_msat(),
+			forwarding_fee_proportional_millionths: c.config.forwarding_fee_proportional_millionths,
+			forwarding_fee_base_msat: c.config.forwarding_fee_base_msat,
+			user_channel_id: c.user_channel_id.0.to_string(),
 		})
 		.collect();
 
@@ -428,6 +1807,8 @@ fn handle_list_channels_request(
 
 fn handle_get_payment_details_request(
 	node: Arc<Node>, request: GetPaymentDetailsRequest,
+	payment_failure_reasons: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
 ) -> Result<protos::PaymentDetails, ldk_node::NodeError> {
 	let payment_id = request.payment_id.as_bytes();
 	if payment_id.len() != 32 {
@@ -435,65 +1816,381 @@ fn handle_get_payment_details_request(
 	}
 
 	let mut arr = [0u8; 32];
-	arr.copy_from_slice(&payment_id[..]);
-	let payment_id = ldk_node::lightning::ln::channelmanager::PaymentId(arr);
+	arr.copy_from_slice(payment_id);
+	let payment_id = PaymentId(arr);
 	if let Some(payment_details) = node.payment(&payment_id) {
-		let response = to_payment_details_proto(&payment_details);
+		let response =
+			to_payment_details_proto(&payment_details, payment_failure_reasons, payment_metadata);
 		return Ok(response);
 	}
 
-	return Err(ldk_node::NodeError::InvalidPaymentId);
+	Err(ldk_node::NodeError::InvalidPaymentId)
+}
+
+fn handle_get_payments_by_hash_request(
+	node: Arc<Node>, request: GetPaymentsByHashRequest,
+	payment_failure_reasons: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> Result<GetPaymentsByHashResponse, ldk_node::NodeError> {
+	let payment_hash = parse_payment_hash(&request.payment_hash)?;
+	let payments = node
+		.list_payments()
+		.iter()
+		.filter(|payment| payment_kind_hash(&payment.kind) == Some(payment_hash))
+		.map(|payment| to_payment_details_proto(payment, payment_failure_reasons, payment_metadata))
+		.collect();
+
+	let response = GetPaymentsByHashResponse { payments };
+	Ok(response)
+}
+
+fn payment_kind_hash(kind: &PaymentKind) -> Option<PaymentHash> {
+	match kind {
+		PaymentKind::Onchain { .. } => None,
+		PaymentKind::Bolt11 { hash, .. } => Some(*hash),
+		PaymentKind::Bolt11Jit { hash, .. } => Some(*hash),
+		PaymentKind::Bolt12Offer { hash, .. } => *hash,
+		PaymentKind::Bolt12Refund { hash, .. } => *hash,
+		PaymentKind::Spontaneous { hash, .. } => Some(*hash),
+	}
+}
+
+fn handle_list_graph_channels_request(
+	node: Arc<Node>, request: ListGraphChannelsRequest,
+) -> Result<ListGraphChannelsResponse, ldk_node::NodeError> {
+	let network_graph = node.network_graph();
+	let mut short_channel_ids = network_graph.list_channels();
+	short_channel_ids.sort_unstable();
+
+	let limit = request.limit.unwrap_or(DEFAULT_GRAPH_PAGE_LIMIT) as usize;
+	let channels = short_channel_ids
+		.iter()
+		.skip(request.offset as usize)
+		.take(limit)
+		.filter_map(|scid| {
+			network_graph.channel(*scid).map(|channel| to_graph_channel_proto(*scid, &channel))
+		})
+		.collect();
+
+	let response =
+		ListGraphChannelsResponse { channels, total_count: short_channel_ids.len() as u64 };
+	Ok(response)
+}
+
+fn handle_get_graph_channel_request(
+	node: Arc<Node>, request: GetGraphChannelRequest,
+) -> Result<GetGraphChannelResponse, ldk_node::NodeError> {
+	let channel = node
+		.network_graph()
+		.channel(request.short_channel_id)
+		.map(|channel| to_graph_channel_proto(request.short_channel_id, &channel));
+	let response = GetGraphChannelResponse { channel };
+	Ok(response)
+}
+
+fn handle_list_graph_nodes_request(
+	node: Arc<Node>, request: ListGraphNodesRequest,
+) -> Result<ListGraphNodesResponse, ldk_node::NodeError> {
+	let network_graph = node.network_graph();
+	let mut node_ids = network_graph.list_nodes();
+	node_ids.sort_unstable();
+
+	let limit = request.limit.unwrap_or(DEFAULT_GRAPH_PAGE_LIMIT) as usize;
+	let nodes = node_ids
+		.iter()
+		.skip(request.offset as usize)
+		.take(limit)
+		.filter_map(|node_id| {
+			network_graph.node(node_id).map(|node_info| to_graph_node_proto(node_id, &node_info))
+		})
+		.collect();
+
+	let response = ListGraphNodesResponse { nodes, total_count: node_ids.len() as u64 };
+	Ok(response)
+}
+
+fn handle_get_graph_node_request(
+	node: Arc<Node>, request: GetGraphNodeRequest,
+) -> Result<GetGraphNodeResponse, ldk_node::NodeError> {
+	let public_key =
+		PublicKey::from_str(&request.node_id).map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
+	let node_id = NodeId::from_pubkey(&public_key);
+	let node = node
+		.network_graph()
+		.node(&node_id)
+		.map(|node_info| to_graph_node_proto(&node_id, &node_info));
+	let response = GetGraphNodeResponse { node };
+	Ok(response)
+}
+
+fn to_graph_channel_proto(short_channel_id: u64, channel: &ChannelInfo) -> GraphChannel {
+	GraphChannel {
+		short_channel_id,
+		node_one: channel.node_one.to_string(),
+		one_to_two: channel.one_to_two.as_ref().map(to_graph_channel_update_proto),
+		node_two: channel.node_two.to_string(),
+		two_to_one: channel.two_to_one.as_ref().map(to_graph_channel_update_proto),
+		capacity_sats: channel.capacity_sats,
+	}
+}
+
+fn to_graph_channel_update_proto(update: &ChannelUpdateInfo) -> GraphChannelUpdate {
+	GraphChannelUpdate {
+		last_update: update.last_update,
+		enabled: update.enabled,
+		cltv_expiry_delta: update.cltv_expiry_delta as u32,
+		htlc_minimum_msat: update.htlc_minimum_msat,
+		htlc_maximum_msat: update.htlc_maximum_msat,
+		fee_base_msat: update.fees.base_msat,
+		fee_proportional_millionths: update.fees.proportional_millionths,
+	}
+}
+
+fn to_graph_node_proto(node_id: &NodeId, node_info: &NodeInfo) -> GraphNode {
+	GraphNode {
+		node_id: node_id.to_string(),
+		short_channel_ids: node_info.channels.clone(),
+		announcement: node_info.announcement_info.as_ref().map(|announcement| {
+			GraphNodeAnnouncement {
+				last_update: announcement.last_update(),
+				alias: announcement.alias().to_string(),
+				rgb: format!(
+					"{:02x}{:02x}{:02x}",
+					announcement.rgb()[0],
+					announcement.rgb()[1],
+					announcement.rgb()[2]
+				),
+				addresses: announcement.addresses().iter().map(|a| a.to_string()).collect(),
+			}
+		}),
+	}
 }
 
 fn handle_open_channel(
 	node: Arc<Node>, request: OpenChannelRequest,
-) -> Result<OpenChannelResponse, ldk_node::NodeError> {
+) -> Result<OpenChannelResponse, HandlerError> {
 	let node_id =
 		PublicKey::from_str(&request.node_id).map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
 	let address = SocketAddress::from_str(&request.address)
 		.map_err(|_| ldk_node::NodeError::InvalidSocketAddress)?;
-	let user_channel_id = node.connect_open_channel(
-		node_id,
-		address,
-		request.channel_amount_sats,
-		request.push_to_counterparty_msat,
-		None,
-		request.announce_channel,
-	)?;
-	let response =
-		OpenChannelResponse { user_channel_id: user_channel_id.0.to_be_bytes().to_vec() };
+	let channel_config = match request.channel_config {
+		Some(config_update) => {
+			let mut channel_config = ChannelConfig::default();
+			apply_channel_config_update(&mut channel_config, config_update)?;
+			Some(channel_config)
+		},
+		None => None,
+	};
+	let user_channel_id = if request.announce_channel {
+		node.open_announced_channel(
+			node_id,
+			address,
+			request.channel_amount_sats,
+			request.push_to_counterparty_msat,
+			channel_config,
+		)?
+	} else {
+		node.open_channel(
+			node_id,
+			address,
+			request.channel_amount_sats,
+			request.push_to_counterparty_msat,
+			channel_config,
+		)?
+	};
+
+	let channel = node.list_channels().into_iter().find(|c| c.user_channel_id == user_channel_id);
+	let response = OpenChannelResponse {
+		user_channel_id: user_channel_id.0.to_string(),
+		channel_id: channel.as_ref().map(|c| c.channel_id.to_string()),
+		funding_txo: channel
+			.and_then(|c| c.funding_txo)
+			.map(|o| Outpoint { txid: o.txid.to_string(), vout: o.vout }),
+	};
 	Ok(response)
 }
 
 fn handle_close_channel(
 	node: Arc<Node>, request: CloseChannelRequest,
 ) -> Result<CloseChannelResponse, ldk_node::NodeError> {
-	let mut be_bytes = [0u8; 16];
-	be_bytes.copy_from_slice(&request.user_channel_id);
-	let user_channel_id = UserChannelId(u128::from_be_bytes(be_bytes));
-	let counterparty_node_id = PublicKey::from_str(&request.counterparty_node_id)
-		.map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
-	node.close_channel(&user_channel_id, counterparty_node_id)?;
-	let response = CloseChannelResponse {};
+	let channels = resolve_channels(
+		node.list_channels(),
+		request.channel,
+		request.counterparty_node_id,
+		request.all_channels,
+	)?;
+	let (user_channel_ids, failures) = close_channels(channels, |channel| {
+		node.close_channel(&channel.user_channel_id, channel.counterparty_node_id)
+	})?;
+	let response = CloseChannelResponse { user_channel_ids, failures };
 	Ok(response)
 }
 
 fn handle_force_close_channel(
 	node: Arc<Node>, request: ForceCloseChannelRequest,
 ) -> Result<ForceCloseChannelResponse, ldk_node::NodeError> {
-	let mut be_bytes = [0u8; 16];
-	be_bytes.copy_from_slice(&request.user_channel_id);
-	let user_channel_id = UserChannelId(u128::from_be_bytes(be_bytes));
+	let channels = resolve_channels(
+		node.list_channels(),
+		request.channel,
+		request.counterparty_node_id,
+		request.all_channels,
+	)?;
+	let (user_channel_ids, failures) = close_channels(channels, |channel| {
+		node.force_close_channel(&channel.user_channel_id, channel.counterparty_node_id, None)
+	})?;
+	let response = ForceCloseChannelResponse { user_channel_ids, failures };
+	Ok(response)
+}
+
+/// Closes each of the given channels via `close`, carrying on past channels that fail to close.
+///
+/// Returns the local channel ids of the closed channels along with the failures, or the first error
+/// if no channel could be closed at all.
+fn close_channels(
+	channels: Vec<ChannelDetails>,
+	close: impl Fn(&ChannelDetails) -> Result<(), ldk_node::NodeError>,
+) -> Result<(Vec<String>, Vec<ChannelCloseFailure>), ldk_node::NodeError> {
+	let mut user_channel_ids = Vec::with_capacity(channels.len());
+	let mut failures = Vec::new();
+	let mut first_error = None;
+	for channel in channels {
+		let user_channel_id = channel.user_channel_id.0.to_string();
+		match close(&channel) {
+			Ok(()) => user_channel_ids.push(user_channel_id),
+			Err(e) => {
+				failures.push(ChannelCloseFailure { user_channel_id, error: e.to_string() });
+				first_error.get_or_insert(e);
+			},
+		}
+	}
+	match first_error {
+		Some(e) if user_channel_ids.is_empty() => Err(e),
+		_ => Ok((user_channel_ids, failures)),
+	}
+}
+
+/// Resolves the channels targeted by a close or force-close request among the given channels.
+///
+/// If `all_channels` is set, this returns all channels with the given counterparty, of which there
+/// must be at least one. Otherwise, it returns the single channel matching the given identifier.
+fn resolve_channels(
+	channels: Vec<ChannelDetails>, channel: Option<protos::ChannelIdentifier>,
+	counterparty_node_id: Option<String>, all_channels: bool,
+) -> Result<Vec<ChannelDetails>, ldk_node::NodeError> {
+	let counterparty_node_id = counterparty_node_id
+		.map(|id| PublicKey::from_str(&id).map_err(|_| ldk_node::NodeError::InvalidNodeId))
+		.transpose()?;
+
+	if all_channels {
+		let counterparty_node_id =
+			counterparty_node_id.ok_or(ldk_node::NodeError::InvalidNodeId)?;
+		let channels = channels
+			.into_iter()
+			.filter(|c| c.counterparty_node_id == counterparty_node_id)
+			.collect::<Vec<_>>();
+		if channels.is_empty() {
+			return Err(ldk_node::NodeError::InvalidChannelId);
+		}
+		return Ok(channels);
+	}
+
+	let channel = match channel.and_then(|c| c.id) {
+		Some(channel_identifier::Id::UserChannelId(user_channel_id)) => {
+			let user_channel_id = parse_user_channel_id(&user_channel_id)?;
+			channels.into_iter().find(|c| c.user_channel_id == user_channel_id)
+		},
+		Some(channel_identifier::Id::ChannelId(channel_id)) => channels
+			.into_iter()
+			.find(|c| c.channel_id.to_string().eq_ignore_ascii_case(&channel_id)),
+		Some(channel_identifier::Id::FundingTxo(outpoint)) => channels.into_iter().find(|c| {
+			c.funding_txo.is_some_and(|txo| {
+				txo.txid.to_string().eq_ignore_ascii_case(&outpoint.txid)
+					&& txo.vout == outpoint.vout
+			})
+		}),
+		None => None,
+	}
+	.ok_or(ldk_node::NodeError::InvalidChannelId)?;
+
+	if let Some(counterparty_node_id) = counterparty_node_id {
+		if channel.counterparty_node_id != counterparty_node_id {
+			return Err(ldk_node::NodeError::InvalidChannelId);
+		}
+	}
+	Ok(vec![channel])
+}
+
+fn handle_update_channel_config(
+	node: Arc<Node>, request: UpdateChannelConfigRequest,
+) -> Result<UpdateChannelConfigResponse, HandlerError> {
+	let user_channel_id = parse_user_channel_id(&request.user_channel_id)?;
 	let counterparty_node_id = PublicKey::from_str(&request.counterparty_node_id)
 		.map_err(|_| ldk_node::NodeError::InvalidNodeId)?;
-	node.force_close_channel(&user_channel_id, counterparty_node_id)?;
-	let response = ForceCloseChannelResponse {};
+
+	// We start from the channel's current config so that any fields not given in the request
+	// retain their value.
+	let channel = node
+		.list_channels()
+		.into_iter()
+		.find(|c| {
+			c.user_channel_id == user_channel_id && c.counterparty_node_id == counterparty_node_id
+		})
+		.ok_or(ldk_node::NodeError::InvalidChannelId)?;
+	let mut channel_config = channel.config;
+	if let Some(config_update) = request.channel_config {
+		apply_channel_config_update(&mut channel_config, config_update)?;
+	}
+
+	node.update_channel_config(&user_channel_id, counterparty_node_id, channel_config)?;
+	let response = UpdateChannelConfigResponse {};
 	Ok(response)
 }
 
+/// Parses a `UserChannelId` from its canonical textual form, i.e., a decimal `u128`.
+fn parse_user_channel_id(user_channel_id: &str) -> Result<UserChannelId, ldk_node::NodeError> {
+	let user_channel_id =
+		u128::from_str(user_channel_id).map_err(|_| ldk_node::NodeError::InvalidChannelId)?;
+	Ok(UserChannelId(user_channel_id))
+}
+
+fn apply_channel_config_update(
+	config: &mut ChannelConfig, update: protos::ChannelConfig,
+) -> Result<(), HandlerError> {
+	if let Some(fee_ppm) = update.forwarding_fee_proportional_millionths {
+		config.forwarding_fee_proportional_millionths = fee_ppm;
+	}
+	if let Some(fee_base_msat) = update.forwarding_fee_base_msat {
+		config.forwarding_fee_base_msat = fee_base_msat;
+	}
+	if let Some(cltv_expiry_delta) = update.cltv_expiry_delta {
+		config.cltv_expiry_delta = u16::try_from(cltv_expiry_delta).map_err(|_| {
+			HandlerError::InvalidRequest(format!(
+				"CLTV expiry delta of {} is out of range",
+				cltv_expiry_delta
+			))
+		})?;
+	}
+	match update.max_dust_htlc_exposure {
+		Some(channel_config::MaxDustHtlcExposure::FixedLimitMsat(limit_msat)) => {
+			config.max_dust_htlc_exposure = MaxDustHTLCExposure::FixedLimit { limit_msat };
+		},
+		Some(channel_config::MaxDustHtlcExposure::FeeRateMultiplier(multiplier)) => {
+			config.max_dust_htlc_exposure = MaxDustHTLCExposure::FeeRateMultiplier { multiplier };
+		},
+		None => {},
+	}
+	if let Some(max_fee_sats) = update.force_close_avoidance_max_fee_satoshis {
+		config.force_close_avoidance_max_fee_satoshis = max_fee_sats;
+	}
+	if let Some(accept_underpaying_htlcs) = update.accept_underpaying_htlcs {
+		config.accept_underpaying_htlcs = accept_underpaying_htlcs;
+	}
+	Ok(())
+}
+
 fn to_payment_kind_proto(kind: &PaymentKind) -> protos::PaymentKind {
 	match kind {
-		ldk_node::payment::PaymentKind::Onchain => protos::PaymentKind {
+		ldk_node::payment::PaymentKind::Onchain { .. } => protos::PaymentKind {
 			kind: Some(protos::payment_kind::Kind::Onchain(protos::Onchain {})),
 		},
 		ldk_node::payment::PaymentKind::Bolt11 { hash, preimage, secret } => protos::PaymentKind {
@@ -503,31 +2200,35 @@ fn to_payment_kind_proto(kind: &PaymentKind) -> protos::PaymentKind {
 				secret: secret.map(|it| it.0.to_vec()),
 			})),
 		},
-		ldk_node::payment::PaymentKind::Bolt11Jit { hash, preimage, secret, lsp_fee_limits } => {
-			protos::PaymentKind {
-				kind: Some(protos::payment_kind::Kind::Bolt11Jit(protos::Bolt11Jit {
-					hash: hash.to_string(),
-					preimage: preimage.map(|it| it.to_string()),
-					secret: secret.map(|it| it.0.to_vec()),
-					lsp_fee_limits: Some(protos::LspFeeLimits {
-						max_total_opening_fee_msat: lsp_fee_limits.max_total_opening_fee_msat,
-						max_proportional_opening_fee_ppm_msat: lsp_fee_limits
-							.max_proportional_opening_fee_ppm_msat,
-					}),
-				})),
-			}
+		ldk_node::payment::PaymentKind::Bolt11Jit {
+			hash,
+			preimage,
+			secret,
+			lsp_fee_limits,
+			..
+		} => protos::PaymentKind {
+			kind: Some(protos::payment_kind::Kind::Bolt11Jit(protos::Bolt11Jit {
+				hash: hash.to_string(),
+				preimage: preimage.map(|it| it.to_string()),
+				secret: secret.map(|it| it.0.to_vec()),
+				lsp_fee_limits: Some(protos::LspFeeLimits {
+					max_total_opening_fee_msat: lsp_fee_limits.max_total_opening_fee_msat,
+					max_proportional_opening_fee_ppm_msat: lsp_fee_limits
+						.max_proportional_opening_fee_ppm_msat,
+				}),
+			})),
 		},
-		ldk_node::payment::PaymentKind::Bolt12Offer { hash, preimage, secret, offer_id } => {
-			protos::PaymentKind {
-				kind: Some(protos::payment_kind::Kind::Bolt12offer(protos::Bolt12Offer {
-					hash: hash.map(|it| it.to_string()),
-					preimage: preimage.map(|it| it.to_string()),
-					secret: secret.map(|it| it.0.to_vec()),
-					offer_id: offer_id.0.to_vec(),
-				})),
-			}
+		ldk_node::payment::PaymentKind::Bolt12Offer {
+			hash, preimage, secret, offer_id, ..
+		} => protos::PaymentKind {
+			kind: Some(protos::payment_kind::Kind::Bolt12offer(protos::Bolt12Offer {
+				hash: hash.map(|it| it.to_string()),
+				preimage: preimage.map(|it| it.to_string()),
+				secret: secret.map(|it| it.0.to_vec()),
+				offer_id: offer_id.0.to_vec(),
+			})),
 		},
-		ldk_node::payment::PaymentKind::Bolt12Refund { hash, preimage, secret } => {
+		ldk_node::payment::PaymentKind::Bolt12Refund { hash, preimage, secret, .. } => {
 			protos::PaymentKind {
 				kind: Some(protos::payment_kind::Kind::Bolt12refund(protos::Bolt12Refund {
 					hash: hash.map(|it| it.to_string()),
@@ -545,7 +2246,236 @@ fn to_payment_kind_proto(kind: &PaymentKind) -> protos::PaymentKind {
 	}
 }
 
-fn to_payment_details_proto(payment: &PaymentDetails) -> protos::PaymentDetails {
+pub(crate) fn to_payment_failure_reason_proto(
+	reason: PaymentFailureReason,
+) -> protos::PaymentFailureReason {
+	match reason {
+		PaymentFailureReason::RecipientRejected => protos::PaymentFailureReason::RecipientRejected,
+		PaymentFailureReason::UserAbandoned => protos::PaymentFailureReason::UserAbandoned,
+		PaymentFailureReason::RetriesExhausted => protos::PaymentFailureReason::RetriesExhausted,
+		PaymentFailureReason::PaymentExpired => protos::PaymentFailureReason::PaymentExpired,
+		PaymentFailureReason::RouteNotFound => protos::PaymentFailureReason::RouteNotFound,
+		PaymentFailureReason::UnexpectedError => protos::PaymentFailureReason::UnexpectedError,
+		PaymentFailureReason::UnknownRequiredFeatures => {
+			protos::PaymentFailureReason::UnknownRequiredFeatures
+		},
+		PaymentFailureReason::InvoiceRequestExpired => {
+			protos::PaymentFailureReason::InvoiceRequestExpired
+		},
+		PaymentFailureReason::InvoiceRequestRejected => {
+			protos::PaymentFailureReason::InvoiceRequestRejected
+		},
+		PaymentFailureReason::BlindedPathCreationFailed => {
+			protos::PaymentFailureReason::BlindedPathCreationFailed
+		},
+	}
+}
+
+fn handle_list_forwards_request(
+	_node: Arc<Node>, request: ListForwardsRequest,
+	forwarded_payments: &JsonLinesLog<ForwardedPayment>,
+) -> Result<ListForwardsResponse, ldk_node::NodeError> {
+	let mut forwards = forwarded_payments
+		.list_entries()
+		.into_iter()
+		.filter(|(_, forward)| {
+			request.start_timestamp.unwrap_or(0) <= forward.timestamp
+				&& forward.timestamp < request.end_timestamp.unwrap_or(u64::MAX)
+		})
+		.collect::<Vec<_>>();
+	// Newest first.
+	forwards.reverse();
+
+	let mut total_fee_earned_msat = 0;
+	let mut total_amount_forwarded_msat = 0;
+	let mut income_by_channel = HashMap::new();
+	let mut income_by_peer = HashMap::new();
+	for (_, forward) in &forwards {
+		total_fee_earned_msat += forward.total_fee_earned_msat.unwrap_or(0);
+		total_amount_forwarded_msat += forward.outbound_amount_forwarded_msat.unwrap_or(0);
+		add_forwarding_income(&mut income_by_channel, &forward.next_channel_id, forward);
+		if let Some(node_id) = &forward.next_counterparty_node_id {
+			add_forwarding_income(&mut income_by_peer, node_id, forward);
+		}
+	}
+
+	let limit = request.limit.unwrap_or(DEFAULT_FORWARDS_PAGE_LIMIT) as usize;
+	let total_count = forwards.len() as u64;
+	let forwards = forwards
+		.into_iter()
+		.skip(request.offset as usize)
+		.take(limit)
+		.map(|(index, forward)| Forward {
+			id: index.to_string(),
+			prev_channel_id: forward.prev_channel_id,
+			next_channel_id: forward.next_channel_id,
+			prev_counterparty_node_id: forward.prev_counterparty_node_id,
+			next_counterparty_node_id: forward.next_counterparty_node_id,
+			total_fee_earned_msat: forward.total_fee_earned_msat,
+			outbound_amount_forwarded_msat: forward.outbound_amount_forwarded_msat,
+			timestamp: forward.timestamp,
+		})
+		.collect();
+
+	let response = ListForwardsResponse {
+		forwards,
+		total_count,
+		total_fee_earned_msat,
+		total_amount_forwarded_msat,
+		income_by_channel: sorted_by_fee_earned(income_by_channel),
+		income_by_peer: sorted_by_fee_earned(income_by_peer),
+	};
+	Ok(response)
+}
+
+fn add_forwarding_income(
+	income: &mut HashMap<String, ForwardingIncome>, id: &str, forward: &ForwardedPayment,
+) {
+	let income = income
+		.entry(id.to_string())
+		.or_insert_with(|| ForwardingIncome { id: id.to_string(), ..Default::default() });
+	income.forward_count += 1;
+	income.fee_earned_msat += forward.total_fee_earned_msat.unwrap_or(0);
+	income.amount_forwarded_msat += forward.outbound_amount_forwarded_msat.unwrap_or(0);
+}
+
+fn sorted_by_fee_earned(income: HashMap<String, ForwardingIncome>) -> Vec<ForwardingIncome> {
+	let mut income = income.into_values().collect::<Vec<_>>();
+	income.sort_unstable_by(|a, b| {
+		b.fee_earned_msat.cmp(&a.fee_earned_msat).then_with(|| a.id.cmp(&b.id))
+	});
+	income
+}
+
+fn handle_export_request(
+	node: Arc<Node>, request: ExportRequest, payment_metadata: &JsonFileStore<PaymentMetadata>,
+	forwarded_payments: &JsonLinesLog<ForwardedPayment>,
+	channel_history: &JsonLinesMap<ChannelRecord>,
+) -> Result<ExportResponse, ldk_node::NodeError> {
+	let in_range = |timestamp: u64| {
+		request.start_timestamp.unwrap_or(0) <= timestamp
+			&& timestamp < request.end_timestamp.unwrap_or(u64::MAX)
+	};
+	let mut records = Vec::new();
+
+	for payment in node.list_payments() {
+		// On-chain payments are exported separately below.
+		if payment.status != PaymentStatus::Succeeded
+			|| matches!(payment.kind, PaymentKind::Onchain { .. })
+			|| !in_range(payment.latest_update_timestamp)
+		{
+			continue;
+		}
+		let amount_msat = payment.amount_msat.unwrap_or(0) as i64;
+		let (category, amount_msat, fee_paid_msat) = match payment.direction {
+			PaymentDirection::Inbound => (ExportCategory::PaymentReceived, amount_msat, 0),
+			PaymentDirection::Outbound => {
+				(ExportCategory::PaymentSent, -amount_msat, payment.fee_paid_msat.unwrap_or(0))
+			},
+		};
+		let payment_id = payment_id_to_hex(&payment.id);
+		let metadata = payment_metadata.get(&payment_id);
+		records.push(ExportRecord {
+			timestamp: payment.latest_update_timestamp,
+			category,
+			id: payment_id,
+			amount_msat,
+			fee_paid_msat,
+			fee_earned_msat: 0,
+			channel_id: None,
+			external_id: metadata.as_ref().and_then(|metadata| metadata.external_id.clone()),
+			memo: metadata.and_then(|metadata| metadata.memo),
+		});
+	}
+
+	for (index, forward) in forwarded_payments.list_entries() {
+		if !in_range(forward.timestamp) {
+			continue;
+		}
+		records.push(ExportRecord {
+			timestamp: forward.timestamp,
+			category: ExportCategory::Forward,
+			id: index.to_string(),
+			amount_msat: 0,
+			fee_paid_msat: 0,
+			fee_earned_msat: forward.total_fee_earned_msat.unwrap_or(0),
+			channel_id: Some(forward.next_channel_id),
+			external_id: None,
+			memo: None,
+		});
+	}
+
+	let (funding_channel_ids, closing_channel_ids) = channel_ids_by_txid(&node, channel_history);
+	for payment in node.list_payments() {
+		let (txid, timestamp) = match payment.kind {
+			PaymentKind::Onchain {
+				txid,
+				status: ConfirmationStatus::Confirmed { timestamp, .. },
+			} if in_range(timestamp) => (txid, timestamp),
+			_ => continue,
+		};
+		let amount_sats = (payment.amount_msat.unwrap_or(0) / 1000) as i64;
+		// The wallet only knows the fee of transactions spending its own outputs.
+		let (net_amount_sats, fee_paid_sats) = match payment.direction {
+			PaymentDirection::Outbound => (-amount_sats, payment.fee_paid_msat.unwrap_or(0) / 1000),
+			PaymentDirection::Inbound => (amount_sats, 0),
+		};
+		let (category, channel_id) = if let Some(channel_id) = funding_channel_ids.get(&txid) {
+			(ExportCategory::ChannelOpen, Some(channel_id.clone()))
+		} else if let Some(channel_id) = closing_channel_ids.get(&txid) {
+			(ExportCategory::ChannelClose, Some(channel_id.clone()))
+		} else if net_amount_sats < 0 {
+			(ExportCategory::OnchainSend, None)
+		} else {
+			(ExportCategory::OnchainReceive, None)
+		};
+		records.push(ExportRecord {
+			timestamp,
+			category,
+			id: txid.to_string(),
+			amount_msat: net_amount_sats * 1000,
+			fee_paid_msat: fee_paid_sats * 1000,
+			fee_earned_msat: 0,
+			channel_id,
+			external_id: None,
+			memo: None,
+		});
+	}
+
+	records.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.id.cmp(&b.id)));
+	let data = match request.format() {
+		ExportFormat::Csv => export::to_csv(&records),
+		ExportFormat::JsonLines => export::to_json_lines(&records),
+	};
+
+	let response = ExportResponse { data };
+	Ok(response)
+}
+
+/// Persists the metadata the caller attached to a new payment, if any.
+fn store_payment_metadata(
+	payment_metadata: &JsonFileStore<PaymentMetadata>, payment_id: &PaymentId,
+	metadata: Option<protos::PaymentMetadata>,
+) {
+	let metadata = match metadata {
+		Some(metadata) => PaymentMetadata {
+			labels: metadata.labels,
+			memo: metadata.memo,
+			external_id: metadata.external_id,
+		},
+		None => return,
+	};
+	let payment_id = payment_id_to_hex(payment_id);
+	if let Err(e) = payment_metadata.insert(payment_id.clone(), metadata) {
+		eprintln!("Failed to persist metadata of payment {}: {}", payment_id, e);
+	}
+}
+
+fn to_payment_details_proto(
+	payment: &PaymentDetails, payment_failure_reasons: &JsonFileStore<String>,
+	payment_metadata: &JsonFileStore<PaymentMetadata>,
+) -> protos::PaymentDetails {
+	let payment_id = payment_id_to_hex(&payment.id);
 	protos::PaymentDetails {
 		id: Some(protos::PaymentId { data: payment.id.0.to_vec() }),
 		kind: Some(to_payment_kind_proto(&payment.kind)),
@@ -560,5 +2490,143 @@ fn to_payment_details_proto(payment: &PaymentDetails) -> protos::PaymentDetails
 			PaymentStatus::Failed => protos::PaymentStatus::Failed.into(),
 		},
 		latest_update_timestamp: payment.latest_update_timestamp,
+		fee_paid_msat: payment.fee_paid_msat,
+		failure_reason: payment_failure_reasons
+			.get(&payment_id)
+			.and_then(|reason| protos::PaymentFailureReason::from_str_name(&reason))
+			.map(|reason| reason.into()),
+		metadata: payment_metadata.get(&payment_id).map(|metadata| protos::PaymentMetadata {
+			labels: metadata.labels,
+			memo: metadata.memo,
+			external_id: metadata.external_id,
+		}),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use ldk_node::bitcoin::OutPoint;
+	use ldk_node::lightning::ln::types::ChannelId;
+
+	use super::*;
+
+	const ALICE: &str = "027100442c3b79f606f80f322d98d499eefcb060599efc5d4ecb00209c2cb54190";
+	const BOB: &str = "03b2c32c52f0b7c3b6b2f4f57c3bcbba1a6a8a52d13e1a3a3d4ee5ac7efc2ee1c1";
+
+	fn channel(user_channel_id: u128, counterparty: &str, funding_vout: u32) -> ChannelDetails {
+		let funding_txid =
+			Txid::from_str("2f5e4cfa2ff4a6ee7f3b1d0f2b2e6c0a7d4b3f2e1c0a9b8d7e6f5a4b3c2d1e0f")
+				.unwrap();
+		ChannelDetails {
+			channel_id: ChannelId([user_channel_id as u8; 32]),
+			counterparty_node_id: PublicKey::from_str(counterparty).unwrap(),
+			funding_txo: Some(OutPoint { txid: funding_txid, vout: funding_vout }),
+			short_channel_id: None,
+			outbound_scid_alias: None,
+			inbound_scid_alias: None,
+			channel_value_sats: 100_000,
+			unspendable_punishment_reserve: None,
+			user_channel_id: UserChannelId(user_channel_id),
+			feerate_sat_per_1000_weight: 253,
+			outbound_capacity_msat: 0,
+			inbound_capacity_msat: 0,
+			confirmations_required: None,
+			confirmations: None,
+			is_outbound: true,
+			is_channel_ready: true,
+			is_usable: true,
+			is_announced: false,
+			cltv_expiry_delta: None,
+			counterparty_unspendable_punishment_reserve: 0,
+			counterparty_outbound_htlc_minimum_msat: None,
+			counterparty_outbound_htlc_maximum_msat: None,
+			counterparty_forwarding_info_fee_base_msat: None,
+			counterparty_forwarding_info_fee_proportional_millionths: None,
+			counterparty_forwarding_info_cltv_expiry_delta: None,
+			next_outbound_htlc_limit_msat: 0,
+			next_outbound_htlc_minimum_msat: 0,
+			force_close_spend_delay: None,
+			inbound_htlc_minimum_msat: 0,
+			inbound_htlc_maximum_msat: None,
+			config: ChannelConfig::default(),
+		}
+	}
+
+	fn channels() -> Vec<ChannelDetails> {
+		vec![channel(1, ALICE, 0), channel(2, ALICE, 1), channel(3, BOB, 2)]
+	}
+
+	fn user_channel_ids(channels: Vec<ChannelDetails>) -> Vec<u128> {
+		channels.into_iter().map(|c| c.user_channel_id.0).collect()
+	}
+
+	fn identifier(id: channel_identifier::Id) -> Option<protos::ChannelIdentifier> {
+		Some(protos::ChannelIdentifier { id: Some(id) })
+	}
+
+	#[test]
+	fn test_parse_user_channel_id() {
+		assert_eq!(parse_user_channel_id("42").unwrap(), UserChannelId(42));
+		assert_eq!(
+			parse_user_channel_id(&u128::MAX.to_string()).unwrap(),
+			UserChannelId(u128::MAX)
+		);
+		assert_eq!(parse_user_channel_id(""), Err(ldk_node::NodeError::InvalidChannelId));
+		assert_eq!(parse_user_channel_id("-1"), Err(ldk_node::NodeError::InvalidChannelId));
+		assert_eq!(parse_user_channel_id("0x2a"), Err(ldk_node::NodeError::InvalidChannelId));
+	}
+
+	#[test]
+	fn test_resolve_channels_by_identifier() {
+		let by_user_channel_id = identifier(channel_identifier::Id::UserChannelId("2".to_string()));
+		let resolved = resolve_channels(channels(), by_user_channel_id, None, false).unwrap();
+		assert_eq!(user_channel_ids(resolved), vec![2]);
+
+		let channel_id = ChannelId([3; 32]).to_string().to_uppercase();
+		let by_channel_id = identifier(channel_identifier::Id::ChannelId(channel_id));
+		let resolved = resolve_channels(channels(), by_channel_id, None, false).unwrap();
+		assert_eq!(user_channel_ids(resolved), vec![3]);
+
+		let funding_txo = channel(0, ALICE, 1).funding_txo.unwrap();
+		let by_funding_txo = identifier(channel_identifier::Id::FundingTxo(Outpoint {
+			txid: funding_txo.txid.to_string(),
+			vout: funding_txo.vout,
+		}));
+		let resolved = resolve_channels(channels(), by_funding_txo, None, false).unwrap();
+		assert_eq!(user_channel_ids(resolved), vec![2]);
+	}
+
+	#[test]
+	fn test_resolve_channels_rejects_unknown_or_mismatched_channels() {
+		let unknown = identifier(channel_identifier::Id::UserChannelId("4".to_string()));
+		let result = resolve_channels(channels(), unknown, None, false);
+		assert_eq!(result.unwrap_err(), ldk_node::NodeError::InvalidChannelId);
+
+		let result = resolve_channels(channels(), None, None, false);
+		assert_eq!(result.unwrap_err(), ldk_node::NodeError::InvalidChannelId);
+
+		// The identified channel must be with the given counterparty, if any.
+		let bobs_channel = identifier(channel_identifier::Id::UserChannelId("3".to_string()));
+		let result = resolve_channels(channels(), bobs_channel, Some(ALICE.to_string()), false);
+		assert_eq!(result.unwrap_err(), ldk_node::NodeError::InvalidChannelId);
+	}
+
+	#[test]
+	fn test_resolve_all_channels_with_counterparty() {
+		let resolved = resolve_channels(channels(), None, Some(ALICE.to_string()), true).unwrap();
+		assert_eq!(user_channel_ids(resolved), vec![1, 2]);
+
+		// The identifier is ignored when closing all channels.
+		let bobs_channel = identifier(channel_identifier::Id::UserChannelId("3".to_string()));
+		let resolved =
+			resolve_channels(channels(), bobs_channel, Some(ALICE.to_string()), true).unwrap();
+		assert_eq!(user_channel_ids(resolved), vec![1, 2]);
+
+		let result = resolve_channels(channels(), None, None, true);
+		assert_eq!(result.unwrap_err(), ldk_node::NodeError::InvalidNodeId);
+
+		let result =
+			resolve_channels(vec![channel(3, BOB, 2)], None, Some(ALICE.to_string()), true);
+		assert_eq!(result.unwrap_err(), ldk_node::NodeError::InvalidChannelId);
 	}
 }
@@ -0,0 +1,422 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A string-keyed map persisted as a JSON file, which is rewritten in full on every update.
+pub(crate) struct JsonFileStore<V> {
+	path: PathBuf,
+	entries: Mutex<HashMap<String, V>>,
+}
+
+impl<V: Serialize + DeserializeOwned + Clone> JsonFileStore<V> {
+	/// Loads the store from the given path, starting out empty if the file doesn't exist yet.
+	pub(crate) fn open(path: PathBuf) -> io::Result<Self> {
+		let entries = match fs::read_to_string(&path) {
+			Ok(contents) => serde_json::from_str(&contents)?,
+			Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+			Err(e) => return Err(e),
+		};
+		Ok(Self { path, entries: Mutex::new(entries) })
+	}
+
+	pub(crate) fn get(&self, key: &str) -> Option<V> {
+		self.entries.lock().unwrap().get(key).cloned()
+	}
+
+	pub(crate) fn list(&self) -> Vec<V> {
+		self.entries.lock().unwrap().values().cloned().collect()
+	}
+
+	pub(crate) fn list_entries(&self) -> Vec<(String, V)> {
+		let entries = self.entries.lock().unwrap();
+		entries.iter().map(|(key, value)| (key.clone(), value.clone())).collect()
+	}
+
+	pub(crate) fn insert(&self, key: String, value: V) -> io::Result<()> {
+		let mut entries = self.entries.lock().unwrap();
+		entries.insert(key, value);
+		self.persist(&entries)
+	}
+
+	pub(crate) fn remove(&self, key: &str) -> io::Result<Option<V>> {
+		let mut entries = self.entries.lock().unwrap();
+		let removed = entries.remove(key);
+		if removed.is_some() {
+			self.persist(&entries)?;
+		}
+		Ok(removed)
+	}
+
+	/// Removes all entries for which `keep` returns false, rewriting the file at most once.
+	pub(crate) fn retain(&self, mut keep: impl FnMut(&str, &V) -> bool) -> io::Result<()> {
+		let mut entries = self.entries.lock().unwrap();
+		let len = entries.len();
+		entries.retain(|key, value| keep(key, value));
+		if entries.len() < len {
+			self.persist(&entries)?;
+		}
+		Ok(())
+	}
+
+	fn persist(&self, entries: &HashMap<String, V>) -> io::Result<()> {
+		// Write to a temporary file first so that a crash can't leave us with a truncated store.
+		let tmp_path = self.path.with_extension("tmp");
+		fs::write(&tmp_path, serde_json::to_vec(entries)?)?;
+		fs::rename(&tmp_path, &self.path)
+	}
+}
+
+/// A list persisted as a JSON Lines file, to which each new entry is appended as a line.
+///
+/// Entries are never modified or removed, so an entry's index, assigned in the order entries were
+/// appended, identifies it for good.
+pub(crate) struct JsonLinesLog<V> {
+	inner: Mutex<JsonLinesLogInner<V>>,
+}
+
+struct JsonLinesLogInner<V> {
+	file: File,
+	entries: Vec<V>,
+}
+
+impl<V: Serialize + DeserializeOwned + Clone> JsonLinesLog<V> {
+	/// Loads the log from the given path, creating the file if it doesn't exist yet.
+	pub(crate) fn open(path: PathBuf) -> io::Result<Self> {
+		let (file, entries) = open_json_lines(&path)?;
+		Ok(Self { inner: Mutex::new(JsonLinesLogInner { file, entries }) })
+	}
+
+	/// Appends the entry, returning its index.
+	pub(crate) fn append(&self, entry: V) -> io::Result<u64> {
+		self.append_with(|_| entry)
+	}
+
+	/// Appends the entry built by `build` from the latest entry, returning its index.
+	///
+	/// The log is locked throughout, so no other entry can be appended in between.
+	pub(crate) fn append_with(&self, build: impl FnOnce(Option<&V>) -> V) -> io::Result<u64> {
+		let mut inner = self.inner.lock().unwrap();
+		let entry = build(inner.entries.last());
+		append_json_line(&mut inner.file, &entry)?;
+		inner.entries.push(entry);
+		Ok(inner.entries.len() as u64 - 1)
+	}
+
+	pub(crate) fn len(&self) -> usize {
+		self.inner.lock().unwrap().entries.len()
+	}
+
+	/// Returns the entries along with their indices, in the order they were appended.
+	pub(crate) fn list_entries(&self) -> Vec<(u64, V)> {
+		let inner = self.inner.lock().unwrap();
+		inner
+			.entries
+			.iter()
+			.cloned()
+			.enumerate()
+			.map(|(index, entry)| (index as u64, entry))
+			.collect()
+	}
+}
+
+/// A string-keyed map persisted as a JSON Lines file, to which each update of an entry is appended
+/// as a line holding its new value. When loading, the latest line for each key wins.
+pub(crate) struct JsonLinesMap<V> {
+	inner: Mutex<JsonLinesMapInner<V>>,
+}
+
+struct JsonLinesMapInner<V> {
+	file: File,
+	entries: HashMap<String, V>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonLinesMapUpdate<V> {
+	key: String,
+	value: V,
+}
+
+impl<V: Serialize + DeserializeOwned + Clone + Default> JsonLinesMap<V> {
+	/// Loads the map from the given path, creating the file if it doesn't exist yet.
+	pub(crate) fn open(path: PathBuf) -> io::Result<Self> {
+		let (file, updates) = open_json_lines::<JsonLinesMapUpdate<V>>(&path)?;
+		let entries = updates.into_iter().map(|update| (update.key, update.value)).collect();
+		Ok(Self { inner: Mutex::new(JsonLinesMapInner { file, entries }) })
+	}
+
+	pub(crate) fn get(&self, key: &str) -> Option<V> {
+		self.inner.lock().unwrap().entries.get(key).cloned()
+	}
+
+	pub(crate) fn list(&self) -> Vec<V> {
+		self.inner.lock().unwrap().entries.values().cloned().collect()
+	}
+
+	pub(crate) fn list_entries(&self) -> Vec<(String, V)> {
+		let inner = self.inner.lock().unwrap();
+		inner.entries.iter().map(|(key, value)| (key.clone(), value.clone())).collect()
+	}
+
+	/// Applies the given update to the entry, starting from its default value if there's none yet.
+	///
+	/// The map is locked throughout, so concurrent updates of the same entry don't overwrite each
+	/// other.
+	pub(crate) fn update(&self, key: String, update: impl FnOnce(&mut V)) -> io::Result<()> {
+		let mut inner = self.inner.lock().unwrap();
+		let mut value = inner.entries.get(&key).cloned().unwrap_or_default();
+		update(&mut value);
+		let line = JsonLinesMapUpdate { key, value };
+		append_json_line(&mut inner.file, &line)?;
+		inner.entries.insert(line.key, line.value);
+		Ok(())
+	}
+}
+
+/// Reads the lines of the given JSON Lines file and opens it for appending, creating it if it
+/// doesn't exist yet.
+///
+/// A trailing line without a newline is left over from a crash mid-append, and is dropped.
+fn open_json_lines<T: DeserializeOwned>(path: &Path) -> io::Result<(File, Vec<T>)> {
+	let contents = match fs::read_to_string(path) {
+		Ok(contents) => contents,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+		Err(e) => return Err(e),
+	};
+	let complete_len = contents.rfind('\n').map_or(0, |index| index + 1);
+	let lines = contents[..complete_len]
+		.lines()
+		.map(serde_json::from_str)
+		.collect::<Result<Vec<T>, _>>()?;
+
+	let file = OpenOptions::new().create(true).append(true).open(path)?;
+	if complete_len < contents.len() {
+		file.set_len(complete_len as u64)?;
+	}
+	Ok((file, lines))
+}
+
+fn append_json_line<T: Serialize>(file: &mut File, value: &T) -> io::Result<()> {
+	let mut line = serde_json::to_vec(value)?;
+	line.push(b'\n');
+	file.write_all(&line)?;
+	file.sync_data()
+}
+
+/// An inbound payment which is held until it is manually claimed or failed.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct ClaimablePayment {
+	pub(crate) claimable_amount_msat: u64,
+	/// The block height by which the payment has to be claimed before it is failed back.
+	pub(crate) claim_deadline: Option<u32>,
+}
+
+/// Caller-defined metadata attached to a payment.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct PaymentMetadata {
+	pub(crate) labels: Vec<String>,
+	pub(crate) memo: Option<String>,
+	pub(crate) external_id: Option<String>,
+}
+
+/// A payment we forwarded, along with the fee we earned for it.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct ForwardedPayment {
+	pub(crate) prev_channel_id: String,
+	pub(crate) next_channel_id: String,
+	/// The node id of the channel counterparty we received the payment from. Unset for HTLCs
+	/// received before the node ran LDK Node v0.5.
+	pub(crate) prev_counterparty_node_id: Option<String>,
+	/// The node id of the channel counterparty we forwarded the payment to. Unset for HTLCs
+	/// received before the node ran LDK Node v0.5.
+	pub(crate) next_counterparty_node_id: Option<String>,
+	/// Unset for forwards claimed on-chain by nodes running older LDK versions.
+	pub(crate) total_fee_earned_msat: Option<u64>,
+	pub(crate) outbound_amount_forwarded_msat: Option<u64>,
+	/// The unix timestamp at which the forward was settled.
+	pub(crate) timestamp: u64,
+}
+
+/// The lifecycle of a channel, recorded from channel events and keyed by user channel id.
+///
+/// Fields are unset if the corresponding event happened before the server started recording
+/// channel history.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct ChannelRecord {
+	pub(crate) channel_id: String,
+	pub(crate) counterparty_node_id: Option<String>,
+	pub(crate) funding_txid: Option<String>,
+	pub(crate) funding_output_index: Option<u32>,
+	pub(crate) channel_value_sats: Option<u64>,
+	pub(crate) is_outbound: Option<bool>,
+	/// The unix timestamp at which the funding transaction was broadcast.
+	pub(crate) pending_at: Option<u64>,
+	/// The unix timestamp at which the channel became usable.
+	pub(crate) ready_at: Option<u64>,
+	pub(crate) closed_at: Option<u64>,
+	pub(crate) closure_reason: Option<String>,
+	/// Looked up asynchronously once the channel closed, so this may lag behind `closed_at`.
+	pub(crate) closing_txid: Option<String>,
+	/// Our balance claimable on-chain when the channel closed, excluding HTLCs.
+	pub(crate) final_balance_sats: Option<u64>,
+	/// The HTLCs we may still be able to claim on-chain when the channel closed.
+	pub(crate) final_pending_htlc_balance_sats: Option<u64>,
+}
+
+/// An automatic sweep of surplus on-chain funds, or a failed attempt at one.
+///
+/// Recorded before the sweep is sent, so if neither `txid` nor `error` is set, the server stopped
+/// while sending it and it may or may not have been sent.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub(crate) struct SweepRecord {
+	pub(crate) timestamp: u64,
+	pub(crate) spendable_balance_sats: u64,
+	pub(crate) amount_sats: u64,
+	pub(crate) address: String,
+	/// The derivation index of the address if it was derived from an xpub.
+	pub(crate) xpub_index: Option<u32>,
+	pub(crate) fee_rate_sat_per_vb: u64,
+	/// Unset if the sweep failed.
+	pub(crate) txid: Option<String>,
+	pub(crate) error: Option<String>,
+}
+
+/// An outgoing payment counted towards the spending limits, keyed by its payment id or txid.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SpendRecord {
+	pub(crate) timestamp: u64,
+	pub(crate) amount_msat: u64,
+	pub(crate) destination: String,
+}
+
+/// An outgoing payment parked until it is approved or rejected.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct ParkedPayment {
+	pub(crate) kind: OutgoingPaymentKind,
+	pub(crate) amount_msat: u64,
+	pub(crate) destination: String,
+	/// The hex-encoded send request, which is replayed once the payment is approved.
+	pub(crate) request: String,
+	pub(crate) created_at: u64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum OutgoingPaymentKind {
+	Bolt11,
+	Bolt12,
+	Spontaneous,
+	Onchain,
+}
+
+impl OutgoingPaymentKind {
+	pub(crate) fn as_str(&self) -> &'static str {
+		match self {
+			OutgoingPaymentKind::Bolt11 => "bolt11",
+			OutgoingPaymentKind::Bolt12 => "bolt12",
+			OutgoingPaymentKind::Spontaneous => "spontaneous",
+			OutgoingPaymentKind::Onchain => "onchain",
+		}
+	}
+}
+
+/// A state-changing API call, chained to the previous entry of the audit log by its hash.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct AuditEntry {
+	pub(crate) sequence: u64,
+	pub(crate) timestamp: u64,
+	/// The address the call came from, as callers aren't authenticated.
+	pub(crate) identity: String,
+	pub(crate) path: String,
+	/// The request with sensitive fields redacted, or empty if the entry records an outcome.
+	pub(crate) request: String,
+	/// Set if the entry records the outcome of a call, to the sequence number of the call's entry.
+	pub(crate) outcome_of: Option<u64>,
+	/// Set if the call failed.
+	pub(crate) error: Option<String>,
+	pub(crate) prev_hash: String,
+	/// The hex-encoded SHA-256 hash of the entry with this field left empty.
+	pub(crate) hash: String,
+}
+
+/// The response to a request carrying an idempotency key, returned again if the request is
+/// repeated with the same key.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct IdempotentResponse {
+	pub(crate) path: String,
+	/// The hex-encoded SHA-256 hash of the request, used to detect a key being reused for a
+	/// different request.
+	pub(crate) request_hash: String,
+	/// The hex-encoded response, unset while the request is being executed.
+	pub(crate) response: Option<String>,
+	pub(crate) created_at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn log_path(name: &str) -> PathBuf {
+		let path = std::env::temp_dir().join(format!("{}_{}.jsonl", name, std::process::id()));
+		let _ = fs::remove_file(&path);
+		path
+	}
+
+	#[test]
+	fn test_json_lines_log_appends_and_reloads_entries() {
+		let path = log_path("json_lines_log_reload");
+		let log = JsonLinesLog::open(path.clone()).unwrap();
+		assert_eq!(log.append("first".to_string()).unwrap(), 0);
+		assert_eq!(log.append("second".to_string()).unwrap(), 1);
+		assert_eq!(fs::read_to_string(&path).unwrap(), "\"first\"\n\"second\"\n");
+
+		let log = JsonLinesLog::<String>::open(path.clone()).unwrap();
+		assert_eq!(log.append("third".to_string()).unwrap(), 2);
+		assert_eq!(
+			log.list_entries(),
+			vec![(0, "first".to_string()), (1, "second".to_string()), (2, "third".to_string())]
+		);
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_json_lines_log_drops_torn_trailing_line() {
+		let path = log_path("json_lines_log_torn");
+		fs::write(&path, "\"first\"\n\"seco").unwrap();
+
+		let log = JsonLinesLog::<String>::open(path.clone()).unwrap();
+		assert_eq!(log.list_entries(), vec![(0, "first".to_string())]);
+		assert_eq!(log.append("second".to_string()).unwrap(), 1);
+		assert_eq!(fs::read_to_string(&path).unwrap(), "\"first\"\n\"second\"\n");
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_json_lines_map_reloads_latest_values() {
+		let path = log_path("json_lines_map_reload");
+		let map = JsonLinesMap::<u64>::open(path.clone()).unwrap();
+		map.update("a".to_string(), |value| *value += 1).unwrap();
+		map.update("b".to_string(), |value| *value = 10).unwrap();
+		map.update("a".to_string(), |value| *value += 1).unwrap();
+		assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+		let map = JsonLinesMap::<u64>::open(path.clone()).unwrap();
+		assert_eq!(map.get("a"), Some(2));
+		assert_eq!(map.get("b"), Some(10));
+		assert_eq!(map.get("c"), None);
+		fs::remove_file(path).unwrap();
+	}
+
+	#[test]
+	fn test_json_lines_log_rejects_corrupt_entries() {
+		let path = log_path("json_lines_log_corrupt");
+		fs::write(&path, "\"first\"\nnot json\n").unwrap();
+
+		assert!(JsonLinesLog::<String>::open(path.clone()).is_err());
+		fs::remove_file(path).unwrap();
+	}
+}
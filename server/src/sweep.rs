@@ -0,0 +1,291 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use ldk_node::bitcoin::bip32::{ChildNumber, Xpub};
+use ldk_node::bitcoin::secp256k1::Secp256k1;
+use ldk_node::bitcoin::{Address, CompressedPublicKey, Network};
+use ldk_node::Node;
+use protos::{onchain_send_request, OnchainSendRequest};
+use tokio::time::{interval_at, Instant};
+
+use crate::audit::AuditLog;
+use crate::config::{AutoSweepConfig, SweepDestination};
+use crate::esplora::FeeRateEstimates;
+use crate::store::{JsonLinesMap, SweepRecord};
+use crate::utils;
+
+/// The identity and path under which sweeps are recorded in the audit log.
+const AUDIT_IDENTITY: &str = "auto-sweep";
+const AUDIT_PATH: &str = "auto-sweep";
+
+/// Periodically sweeps our spendable on-chain balance above the configured threshold to the
+/// configured destination, recording every attempt in the sweep log and the audit log.
+pub(crate) async fn run_auto_sweep(
+	node: Arc<Node>, config: AutoSweepConfig, sweep_log: Arc<JsonLinesMap<SweepRecord>>,
+	fee_rate_estimates: Arc<FeeRateEstimates>, audit_log: Arc<AuditLog>,
+) {
+	// Don't check right away, so that the wallet has a chance to sync first.
+	let period = Duration::from_secs(config.check_interval_secs);
+	let mut interval = interval_at(Instant::now() + period, period);
+	loop {
+		interval.tick().await;
+		sweep_surplus(&node, &config, &sweep_log, &fee_rate_estimates, &audit_log);
+	}
+}
+
+fn sweep_surplus(
+	node: &Node, config: &AutoSweepConfig, sweep_log: &JsonLinesMap<SweepRecord>,
+	fee_rate_estimates: &FeeRateEstimates, audit_log: &AuditLog,
+) {
+	let spendable_balance_sats = node.list_balances().spendable_onchain_balance_sats;
+	let amount_sats = match sweep_amount(config, spendable_balance_sats) {
+		Some(amount_sats) => amount_sats,
+		None => return,
+	};
+
+	let fee_rate = match fee_rate_estimates.fee_rate(config.confirmation_target_blocks) {
+		Some(fee_rate) => fee_rate,
+		None => {
+			eprintln!("Failed to estimate fee rate for auto-sweep: no fee rate estimates yet");
+			return;
+		},
+	};
+	let fee_rate_sat_per_vb = fee_rate.to_sat_per_vb_ceil();
+	if fee_rate_sat_per_vb > config.max_fee_rate_sat_per_vb {
+		println!(
+			"Deferring auto-sweep of {} sats as the fee rate of {} sat/vB exceeds the maximum of {} sat/vB",
+			amount_sats, fee_rate_sat_per_vb, config.max_fee_rate_sat_per_vb
+		);
+		return;
+	}
+
+	let sweeps = sweep_log.list_entries();
+	let (address, xpub_index) = match &config.destination {
+		SweepDestination::Address(address) => (address.clone(), None),
+		SweepDestination::Xpub(xpub) => {
+			let index = next_xpub_index(&sweeps);
+			match derive_address(xpub, index, node.config().network) {
+				Some(address) => (address, Some(index)),
+				None => {
+					eprintln!("Failed to derive auto-sweep address {} from {}", index, xpub);
+					return;
+				},
+			}
+		},
+	};
+
+	// The sweep is recorded before it is sent, so that its address isn't reused and the sweep isn't
+	// left unaudited even if recording its result fails, and isn't sent if that fails.
+	let mut sweep = SweepRecord {
+		timestamp: utils::unix_timestamp(),
+		spendable_balance_sats,
+		amount_sats,
+		address: address.to_string(),
+		xpub_index,
+		fee_rate_sat_per_vb,
+		txid: None,
+		error: None,
+	};
+	let key = record_key(&sweeps, &sweep.address);
+	if let Err(e) = sweep_log.update(key.clone(), |record| *record = sweep.clone()) {
+		eprintln!("Failed to persist auto-sweep, not sweeping: {}", e);
+		return;
+	}
+	// Sweeps are recorded like the equivalent API call.
+	let request = OnchainSendRequest {
+		address: sweep.address.clone(),
+		amount_sats: Some(amount_sats),
+		fee_rate: Some(onchain_send_request::FeeRate::FeeRateSatPerVb(fee_rate_sat_per_vb)),
+	};
+	let call_sequence = match audit_log.record_call(AUDIT_IDENTITY, AUDIT_PATH, &request) {
+		Ok(call_sequence) => call_sequence,
+		Err(e) => {
+			eprintln!("Failed to record auto-sweep in the audit log, not sweeping: {}", e);
+			sweep.error = Some(format!("Failed to record sweep in the audit log: {}", e));
+			if let Err(e) = sweep_log.update(key, |record| *record = sweep) {
+				eprintln!("Failed to persist auto-sweep: {}", e);
+			}
+			return;
+		},
+	};
+
+	match node.onchain_payment().send_to_address(&address, amount_sats, Some(fee_rate)) {
+		Ok(txid) => {
+			println!("AUTO_SWEEP: swept {} sats to {} in {}", amount_sats, address, txid);
+			sweep.txid = Some(txid.to_string());
+		},
+		Err(e) => {
+			eprintln!("Failed to auto-sweep {} sats to {}: {}", amount_sats, address, e);
+			sweep.error = Some(e.to_string());
+		},
+	}
+	let audit_result =
+		audit_log.record_outcome(AUDIT_IDENTITY, AUDIT_PATH, call_sequence, sweep.error.clone());
+	if let Err(e) = audit_result {
+		eprintln!("Failed to record auto-sweep outcome in the audit log: {}", e);
+	}
+	if let Err(e) = sweep_log.update(key, |record| *record = sweep) {
+		eprintln!("Failed to persist auto-sweep result: {}", e);
+	}
+}
+
+/// The index of the xpub address to sweep to next: the one after the last one we swept to, or may
+/// have swept to, so that addresses aren't reused.
+fn next_xpub_index(sweeps: &[(String, SweepRecord)]) -> u32 {
+	sweeps
+		.iter()
+		.filter(|(_, sweep)| sweep.error.is_none())
+		.filter_map(|(_, sweep)| sweep.xpub_index)
+		.max()
+		.map_or(0, |index| index + 1)
+}
+
+/// The key under which to record a sweep to the given address.
+///
+/// Sweeps are keyed by the order they were attempted in. A failure usually repeats on every check
+/// until something changes, e.g., the wallet having synced, so a sweep retrying a failed one to the
+/// same address replaces its record rather than adding one.
+fn record_key(sweeps: &[(String, SweepRecord)], address: &str) -> String {
+	let last_sweep = sweeps
+		.iter()
+		.filter_map(|(key, sweep)| Some((key.parse::<u64>().ok()?, sweep)))
+		.max_by_key(|(index, _)| *index);
+	match last_sweep {
+		Some((index, sweep)) if sweep.error.is_some() && sweep.address == address => {
+			index.to_string()
+		},
+		Some((index, _)) => (index + 1).to_string(),
+		None => "0".to_string(),
+	}
+}
+
+/// The surplus to sweep given our spendable balance, if it is large enough to be worth sweeping.
+fn sweep_amount(config: &AutoSweepConfig, spendable_balance_sats: u64) -> Option<u64> {
+	let amount_sats = spendable_balance_sats.checked_sub(config.threshold_sats)?;
+	if amount_sats == 0 || amount_sats < config.min_sweep_amount_sats {
+		return None;
+	}
+	Some(amount_sats)
+}
+
+/// Derives the P2WPKH address at the given index of the external chain of the xpub.
+fn derive_address(xpub: &Xpub, index: u32, network: Network) -> Option<Address> {
+	let path = [ChildNumber::from_normal_idx(0).ok()?, ChildNumber::from_normal_idx(index).ok()?];
+	let child = xpub.derive_pub(&Secp256k1::verification_only(), &path).ok()?;
+	Some(Address::p2wpkh(&CompressedPublicKey(child.public_key), network))
+}
+
+#[cfg(test)]
+mod tests {
+	use core::str::FromStr;
+
+	use ldk_node::bitcoin::bip32::{DerivationPath, Xpriv};
+	use ldk_node::bitcoin::hashes::hex::FromHex;
+
+	use super::*;
+
+	fn sweep_config(threshold_sats: u64, min_sweep_amount_sats: u64) -> AutoSweepConfig {
+		let address = Address::from_str("bcrt1qw508d6qejxtdg4y5r3zarvary0c5xw7kygt080").unwrap();
+		AutoSweepConfig {
+			threshold_sats,
+			min_sweep_amount_sats,
+			destination: SweepDestination::Address(address.assume_checked()),
+			max_fee_rate_sat_per_vb: 20,
+			confirmation_target_blocks: 6,
+			check_interval_secs: 600,
+		}
+	}
+
+	fn sweep(
+		address: &str, xpub_index: Option<u32>, txid: Option<&str>, error: Option<&str>,
+	) -> SweepRecord {
+		SweepRecord {
+			timestamp: 0,
+			spendable_balance_sats: 150_000,
+			amount_sats: 50_000,
+			address: address.to_string(),
+			xpub_index,
+			fee_rate_sat_per_vb: 2,
+			txid: txid.map(str::to_string),
+			error: error.map(str::to_string),
+		}
+	}
+
+	#[test]
+	fn test_sweep_amount() {
+		let config = sweep_config(100_000, 10_000);
+		assert_eq!(sweep_amount(&config, 0), None);
+		assert_eq!(sweep_amount(&config, 100_000), None);
+		assert_eq!(sweep_amount(&config, 109_999), None);
+		assert_eq!(sweep_amount(&config, 110_000), Some(10_000));
+		assert_eq!(sweep_amount(&config, 250_000), Some(150_000));
+
+		// Without a minimum, any surplus is swept.
+		let config = sweep_config(100_000, 0);
+		assert_eq!(sweep_amount(&config, 100_000), None);
+		assert_eq!(sweep_amount(&config, 100_001), Some(1));
+	}
+
+	#[test]
+	fn test_next_xpub_index() {
+		assert_eq!(next_xpub_index(&[]), 0);
+
+		let sweeps = vec![
+			("0".to_string(), sweep("a0", Some(0), Some("txid"), None)),
+			("1".to_string(), sweep("a1", Some(1), Some("txid"), None)),
+		];
+		assert_eq!(next_xpub_index(&sweeps), 2);
+
+		// The address of a failed sweep is used again.
+		let mut failed = sweeps.clone();
+		failed.push(("2".to_string(), sweep("a2", Some(2), None, Some("Insufficient funds"))));
+		assert_eq!(next_xpub_index(&failed), 2);
+
+		// The address of a sweep whose result wasn't recorded may have been swept to.
+		let mut unknown = sweeps;
+		unknown.push(("2".to_string(), sweep("a2", Some(2), None, None)));
+		assert_eq!(next_xpub_index(&unknown), 3);
+	}
+
+	#[test]
+	fn test_record_key() {
+		assert_eq!(record_key(&[], "address"), "0");
+
+		let failure = sweep("address", None, None, Some("Insufficient funds"));
+		let mut sweeps = vec![
+			("9".to_string(), sweep("address", None, Some("txid"), None)),
+			("10".to_string(), failure.clone()),
+		];
+		// A sweep retrying a failed one to the same address replaces its record.
+		assert_eq!(record_key(&sweeps, "address"), "10");
+		assert_eq!(record_key(&sweeps, "other"), "11");
+
+		sweeps.push(("11".to_string(), sweep("address", None, Some("txid"), None)));
+		assert_eq!(record_key(&sweeps, "address"), "12");
+		sweeps.push(("12".to_string(), sweep("address", None, None, None)));
+		assert_eq!(record_key(&sweeps, "address"), "13");
+	}
+
+	#[test]
+	fn test_derive_address() {
+		// The BIP 84 test vector for the mnemonic "abandon abandon ... about".
+		let seed = Vec::<u8>::from_hex(
+			"5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1\
+			 9a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4",
+		)
+		.unwrap();
+		let secp = Secp256k1::new();
+		let account_path = DerivationPath::from_str("m/84'/0'/0'").unwrap();
+		let account_xprv =
+			Xpriv::new_master(Network::Bitcoin, &seed).unwrap().derive_priv(&secp, &account_path);
+		let xpub = Xpub::from_priv(&secp, &account_xprv.unwrap());
+
+		let address = derive_address(&xpub, 0, Network::Bitcoin).unwrap();
+		assert_eq!(address.to_string(), "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+		let address = derive_address(&xpub, 1, Network::Bitcoin).unwrap();
+		assert_eq!(address.to_string(), "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+
+		// Hardened indices can't be derived from an xpub.
+		assert_eq!(derive_address(&xpub, 1 << 31, Network::Bitcoin), None);
+	}
+}
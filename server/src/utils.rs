@@ -1,7 +1,9 @@
 use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::config::{Config, JsonConfig};
 use anyhow::Result;
+use ldk_node::lightning::ln::channelmanager::PaymentId;
 
 pub fn read_config_from_json<P: AsRef<Path>>(config_path: P) -> Result<Config> {
 	let contents = std::fs::read_to_string(config_path.as_ref())?;
@@ -9,13 +11,27 @@ pub fn read_config_from_json<P: AsRef<Path>>(config_path: P) -> Result<Config> {
 	Ok(Config::from(json_config))
 }
 
+pub(crate) fn payment_id_to_hex(payment_id: &PaymentId) -> String {
+	to_hex(&payment_id.0)
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn unix_timestamp() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
 #[cfg(test)]
 mod tests {
 	use std::{net::SocketAddr, str::FromStr};
 
-	use ldk_node::{bitcoin::Network, lightning::ln::msgs::SocketAddress, LogLevel};
+	use ldk_node::logger::LogLevel;
+	use ldk_node::{bitcoin::Network, lightning::ln::msgs::SocketAddress};
 
 	use super::*;
+	use crate::config::{GossipSourceConfig, InboundChannelsConfig, SpendingPolicyConfig};
 
 	#[test]
 	fn test_read_json_config_from_file() {
@@ -42,6 +58,10 @@ mod tests {
 				network: Network::Regtest,
 				rest_service_addr: SocketAddr::from_str("127.0.0.1:3002").unwrap(),
 				storage_dir_path: "/tmp".to_string(),
+				inbound_channels: InboundChannelsConfig::default(),
+				gossip_source: GossipSourceConfig::P2p,
+				auto_sweep: None,
+				spending_policy: SpendingPolicyConfig::default(),
 			}
 		)
 	}